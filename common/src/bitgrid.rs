@@ -0,0 +1,148 @@
+use std::ops::{BitAnd, BitOr};
+
+use crate::grid::{Grid2D, GridIterator, Point};
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    width: usize,
+    height: usize,
+}
+
+impl Grid2D for BitGrid {
+    fn width(&self) -> usize { self.width }
+    fn height(&self) -> usize { self.height }
+    fn valid_coordinate(&self, p: &Point) -> bool {
+        p.x < self.width && p.y < self.height
+    }
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { words: vec![0; (width * height).div_ceil(64)], width, height }
+    }
+
+    pub fn with_size_of<G: Grid2D>(grid: &G) -> Self {
+        Self::new(grid.width(), grid.height())
+    }
+
+    fn bit(&self, p: &Point) -> Option<(usize, u64)> {
+        if self.valid_coordinate(p) {
+            let index = p.y * self.width + p.x;
+            Some((index / 64, 1 << (index % 64)))
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: &Point) -> bool {
+        self.bit(p).is_some_and(|(word, mask)| self.words[word] & mask != 0)
+    }
+
+    pub fn set(&mut self, p: &Point, value: bool) {
+        let (word, mask) = self.bit(p).unwrap_or_else(|| panic!("{} is outside the {}x{} grid", p, self.width, self.height));
+        if value {
+            self.words[word] |= mask;
+        }
+        else {
+            self.words[word] &= !mask;
+        }
+    }
+
+    // Like HashSet::insert: true if the point wasn't already set.
+    pub fn insert(&mut self, p: &Point) -> bool {
+        let was_set = self.get(p);
+        self.set(p, true);
+        !was_set
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        GridIterator::new(self).filter(|p| self.get(p))
+    }
+
+    fn check_same_size(&self, other: &BitGrid) {
+        if (self.width, self.height) != (other.width, other.height) {
+            panic!("Cannot combine a {}x{} BitGrid with a {}x{} one", self.width, self.height, other.width, other.height);
+        }
+    }
+
+    pub fn union_with(&mut self, other: &BitGrid) {
+        self.check_same_size(other);
+        self.words.iter_mut().zip(other.words.iter()).for_each(|(a, b)| *a |= b);
+    }
+
+    pub fn intersect_with(&mut self, other: &BitGrid) {
+        self.check_same_size(other);
+        self.words.iter_mut().zip(other.words.iter()).for_each(|(a, b)| *a &= b);
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+    fn bitor(self, rhs: &BitGrid) -> Self::Output {
+        let mut result = self.clone();
+        result.union_with(rhs);
+        result
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+    fn bitand(self, rhs: &BitGrid) -> Self::Output {
+        let mut result = self.clone();
+        result.intersect_with(rhs);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get() {
+        let mut bits = BitGrid::new(140, 140);
+        let p = Point{x:139, y:139};
+        assert!(!bits.get(&p));
+        assert!(bits.insert(&p));
+        assert!(!bits.insert(&p));
+        assert!(bits.get(&p));
+        bits.set(&Point{x:63, y:0}, true);
+        bits.set(&Point{x:64, y:0}, true);
+        assert_eq!(3, bits.count());
+        bits.set(&Point{x:63, y:0}, false);
+        assert_eq!(vec![Point{x:64, y:0}, p], bits.iter().collect::<Vec<_>>());
+        assert!(!bits.get(&Point{x:140, y:0}));
+        bits.clear();
+        assert_eq!(0, bits.count());
+    }
+
+    #[test]
+    fn combine() {
+        let mut a = BitGrid::new(3, 3);
+        let mut b = BitGrid::new(3, 3);
+        a.insert(&Point{x:0, y:0});
+        a.insert(&Point{x:1, y:1});
+        b.insert(&Point{x:1, y:1});
+        b.insert(&Point{x:2, y:2});
+        assert_eq!(3, (&a | &b).count());
+        assert_eq!(vec![Point{x:1, y:1}], (&a & &b).iter().collect::<Vec<_>>());
+        a.union_with(&b);
+        assert_eq!(3, a.count());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot combine a 3x3 BitGrid with a 2x2 one")]
+    fn combine_mismatched() {
+        let _ = &BitGrid::new(3, 3) | &BitGrid::new(2, 2);
+    }
+}
@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, multispace0};
+use nom::combinator::{all_consuming, map_res, opt, recognize};
+use nom::error::Error;
+use nom::multi::separated_list1;
+use nom::sequence::{pair, preceded};
+use nom::{Finish, IResult, Parser};
+
+// An unsigned or, with a leading '-', signed integer of any type that parses from its digits.
+pub fn number<T: FromStr>(input: &str) -> IResult<&str, T> {
+    map_res(recognize(pair(opt(tag("-")), digit1)), str::parse)(input)
+}
+
+// One or more items with sep between each, e.g. list(", ", number) for "1, 2, 3".
+pub fn list<'a, O, F>(sep: &'static str, item: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where
+    F: Parser<&'a str, O, Error<&'a str>>,
+{
+    separated_list1(tag(sep), item)
+}
+
+// body after label and any whitespace, so the same combinator reads "Time:   7  15" and
+// "seed-to-soil map:\n50 98 2".
+pub fn labelled<'a, O, F>(label: &'static str, body: F) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where
+    F: Parser<&'a str, O, Error<&'a str>>,
+{
+    preceded(pair(tag(label), multispace0), body)
+}
+
+// Runs parser over the whole of input, which must leave nothing over. The error owns the
+// unparsed text so it can outlive the input.
+pub fn parse_all<'a, O, F>(parser: F, input: &'a str) -> Result<O, Error<String>>
+where
+    F: Parser<&'a str, O, Error<&'a str>>,
+{
+    all_consuming(parser)(input).finish()
+        .map(|(_, o)| o)
+        .map_err(|e| Error::new(e.input.to_string(), e.code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::character::complete::space1;
+    use nom::error::ErrorKind;
+    use nom::multi::separated_list0;
+
+    #[test]
+    fn combinators() {
+        assert_eq!(Ok((" rest", 42u64)), number("42 rest"));
+        assert_eq!(Ok(("", -7i32)), number("-7"));
+        assert!(number::<u8>("256").is_err());
+        assert_eq!(Ok(("; 4", vec![1u64, 2, 3])), list(", ", number)("1, 2, 3; 4"));
+        let times = labelled("Time:", separated_list0(space1, number::<u64>));
+        assert_eq!(Ok(vec![7, 15, 30]), parse_all(times, "Time:      7  15   30"));
+        let seeds = labelled("seed-to-soil map:", list(" ", number::<u64>));
+        assert_eq!(Ok(vec![50, 98, 2]), parse_all(seeds, "seed-to-soil map:\n50 98 2"));
+        assert_eq!(Err(Error::new(" x".to_string(), ErrorKind::Eof)), parse_all(list(",", number::<u64>), "1,2 x"));
+    }
+}
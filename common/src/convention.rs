@@ -0,0 +1,103 @@
+use crate::direction::Direction;
+use crate::grid::{AoCGrid, Grid, Grid2D, Point};
+
+// Storage is always y-down with the origin top-left, as lines are read; YUp puts the
+// origin bottom-left with y increasing upwards.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Convention {
+    #[default]
+    YDown,
+    YUp,
+}
+
+impl Convention {
+    // Converting is its own inverse, so this maps both to and from storage. None when p is above
+    // a grid of this height, as it has no row to flip onto.
+    pub fn convert(&self, p: &Point, height: usize) -> Option<Point> {
+        match self {
+            Convention::YDown => Some(*p),
+            Convention::YUp => Some(Point { x: p.x, y: height.checked_sub(1)?.checked_sub(p.y)? }),
+        }
+    }
+
+    pub fn delta(&self, d: Direction) -> (isize, isize) {
+        let (dx, dy) = d.delta();
+        match self {
+            Convention::YDown => (dx, dy),
+            Convention::YUp => (dx, -dy),
+        }
+    }
+}
+
+pub struct Oriented<'g, G> {
+    grid: &'g G,
+    convention: Convention,
+}
+
+impl<'g, G: Grid2D> Oriented<'g, G> {
+    pub fn new(grid: &'g G, convention: Convention) -> Self {
+        Self { grid, convention }
+    }
+
+    pub fn to_storage(&self, p: &Point) -> Option<Point> {
+        self.convention.convert(p, self.grid.height())
+    }
+
+    pub fn from_storage(&self, p: &Point) -> Option<Point> {
+        self.convention.convert(p, self.grid.height())
+    }
+
+    pub fn step(&self, p: &Point, d: Direction) -> Option<Point> {
+        let (dx, dy) = self.convention.delta(d);
+        p.offset(dx, dy).filter(|n| self.valid_coordinate(n))
+    }
+}
+
+impl<'g, G: Grid2D> Grid2D for Oriented<'g, G> {
+    fn width(&self) -> usize { self.grid.width() }
+    fn height(&self) -> usize { self.grid.height() }
+    fn valid_coordinate(&self, p: &Point) -> bool {
+        self.grid.valid_coordinate(p)
+    }
+}
+
+impl<'g, T> Oriented<'g, Grid<T>> {
+    pub fn get(&self, p: &Point) -> Option<&'g T> {
+        if self.valid_coordinate(p) { self.grid.get(&self.to_storage(p)?) } else { None }
+    }
+}
+
+impl<'g> Oriented<'g, AoCGrid<'g>> {
+    pub fn get(&self, p: &Point) -> Option<&'g str> {
+        if self.valid_coordinate(p) { self.grid.get(&self.to_storage(p)?) } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn y_up() {
+        let grid = AoCGrid::new("ab\ncd\nef");
+        let up = Oriented::new(&grid, Convention::YUp);
+        assert_eq!(Some("e"), up.get(&Point{x:0,y:0}));
+        assert_eq!(Some("b"), up.get(&Point{x:1,y:2}));
+        assert_eq!(None, up.get(&Point{x:0,y:3}));
+        assert_eq!(Some(Point{x:1,y:2}), up.to_storage(&Point{x:1,y:0}));
+        assert_eq!(Some(Point{x:1,y:0}), up.to_storage(&Point{x:1,y:0}).and_then(|p| up.from_storage(&p)));
+        assert_eq!(None, up.to_storage(&Point{x:0,y:3}));
+        assert_eq!(None, Convention::YUp.convert(&Point{x:0,y:0}, 0));
+        assert_eq!(Some(Point{x:0,y:1}), up.step(&Point{x:0,y:0}, Direction::North));
+        assert_eq!(None, up.step(&Point{x:0,y:0}, Direction::South));
+        assert_eq!(None, up.step(&Point{x:0,y:2}, Direction::North));
+    }
+
+    #[test]
+    fn y_down() {
+        let grid = Grid::from_chars("ab\ncd");
+        let down = Oriented::new(&grid, Convention::default());
+        assert_eq!(Some(&'b'), down.get(&Point{x:1,y:0}));
+        assert_eq!(Some(Point{x:1,y:1}), down.step(&Point{x:1,y:0}, Direction::South));
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::grid::GridError;
+
+// A parse error pinned to part of one input line, rendered like a compiler error with the line
+// quoted and that part underlined. Lines are numbered from 1; columns count chars from 0.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize,
+    pub source_line: String,
+    pub columns: Range<usize>,
+    pub label: String,
+}
+
+// Errors that can say where in the input they went wrong.
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self, source: &str) -> Diagnostic;
+}
+
+impl Diagnostic {
+    // A line past the end of source quotes as empty.
+    pub fn new(source: &str, line: usize, columns: Range<usize>, message: &str, label: &str) -> Self {
+        let source_line = source.lines().nth(line.saturating_sub(1)).unwrap_or("").to_string();
+        Self { message: message.to_string(), line, source_line, columns, label: label.to_string() }
+    }
+
+    // Only says which line, for errors that have lost track of the input. Renders without an
+    // excerpt, as does any diagnostic with no columns.
+    pub fn at_line(line: usize, message: &str) -> Self {
+        Self { message: message.to_string(), line, source_line: String::new(), columns: 0..0, label: String::new() }
+    }
+
+    // Underlines the whole line, or the spot just past it if it is empty.
+    pub fn whole_line(source: &str, line: usize, message: &str, label: &str) -> Self {
+        let mut diagnostic = Self::new(source, line, 0..0, message, label);
+        let len = diagnostic.source_line.chars().count();
+        diagnostic.columns = if len == 0 { 0..1 } else { 0..len };
+        diagnostic
+    }
+
+    // Underlines the first place token appears on the line. An empty token points just past the
+    // end of the line, where the missing text should have been; one that can't be found falls
+    // back to the whole line.
+    pub fn at_token(source: &str, line: usize, token: &str, message: &str, label: &str) -> Self {
+        let mut diagnostic = Self::whole_line(source, line, message, label);
+        let source_line = &diagnostic.source_line;
+        if token.is_empty() {
+            let len = source_line.chars().count();
+            diagnostic.columns = len..len + 1;
+        }
+        else if let Some(at) = source_line.find(token) {
+            let start = source_line[..at].chars().count();
+            diagnostic.columns = start..start + token.chars().count();
+        }
+        diagnostic
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "error: {}", self.message)?;
+        if self.columns.is_empty() {
+            return write!(f, "{}--> line {}", gutter, self.line);
+        }
+        writeln!(f, "{}--> line {}, column {}", gutter, self.line, self.columns.start + 1)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        let underline = "^".repeat(self.columns.len().max(1));
+        write!(f, "{} | {}{} {}", gutter, " ".repeat(self.columns.start), underline, self.label)
+    }
+}
+
+impl ToDiagnostic for GridError {
+    fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        let message = self.to_string();
+        match self {
+            GridError::Empty => Diagnostic::whole_line(source, 1, &message, "no lines"),
+            GridError::NonAscii { at, .. } => Diagnostic::new(source, at.y + 1, at.x..at.x + 1, &message, "not ASCII"),
+            GridError::RaggedLine { y, expected, .. } => Diagnostic::whole_line(source, y + 1, &message, &format!("expected {} cells", expected)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::AoCGrid;
+
+    #[test]
+    fn render() {
+        let source = "Card 1: 1 2 | 3\nCard 2: 4 x5 | 6";
+        let diagnostic = Diagnostic::at_token(source, 2, "x5", "\"x5\" is not a valid number", "not a number");
+        assert_eq!(10..12, diagnostic.columns);
+        assert_eq!("error: \"x5\" is not a valid number
+ --> line 2, column 11
+  |
+2 | Card 2: 4 x5 | 6
+  |           ^^ not a number", diagnostic.to_string());
+        assert_eq!(16..17, Diagnostic::at_token(source, 2, "", "", "").columns);
+        assert_eq!(0..16, Diagnostic::at_token(source, 2, "7", "", "").columns);
+        assert_eq!("error: no seeds\n  --> line 10", Diagnostic::at_line(10, "no seeds").to_string());
+        let past_end = Diagnostic::whole_line(source, 12, "", "");
+        assert_eq!(("", 0..1), (past_end.source_line.as_str(), past_end.columns));
+        let source = "...\n.é.";
+        let error = AoCGrid::try_new(source).map(|_| ()).unwrap_err().to_diagnostic(source);
+        assert_eq!((2, 1..2), (error.line, error.columns));
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+
+use crate::grid::{Grid, Grid2D, Point};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct GridDiff {
+    pub points: Vec<Point>,
+    pub rendering: String,
+}
+
+impl GridDiff {
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+impl fmt::Display for GridDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rendering)
+    }
+}
+
+// Renders `left | right | markers` per row, with an X marking each differing cell.
+// Grids of different sizes are compared over the larger extent; missing cells differ.
+pub fn grid_diff<T: PartialEq + fmt::Display>(a: &Grid<T>, b: &Grid<T>) -> GridDiff {
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+    let cell_width = a.rows().chain(b.rows()).flatten().map(|c| c.to_string().chars().count()).max().unwrap_or(1);
+    let render = |cell: Option<&T>| format!("{:>w$}", cell.map(|c| c.to_string()).unwrap_or_default(), w = cell_width);
+
+    let mut points = Vec::new();
+    let mut rendering = String::new();
+    for y in 0..height {
+        let (mut left, mut right, mut markers) = (String::new(), String::new(), String::new());
+        for x in 0..width {
+            let p = Point { x, y };
+            let (ca, cb) = (a.get(&p), b.get(&p));
+            let differs = ca != cb;
+            if differs {
+                points.push(p);
+            }
+            left.push_str(&render(ca));
+            right.push_str(&render(cb));
+            markers.push_str(&format!("{:>w$}", if differs { "X" } else { " " }, w = cell_width));
+        }
+        rendering.push_str(format!("{} | {} | {}", left, right, markers).trim_end());
+        rendering.push('\n');
+    }
+    GridDiff { points, rendering }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::AoCGrid;
+
+    #[test]
+    fn diff() {
+        let a = AoCGrid::new("#..\n.o.\n...").to_grid(|s| s.chars().next().unwrap());
+        let b = AoCGrid::new("#..\n...\n..o").to_grid(|s| s.chars().next().unwrap());
+        let diff = grid_diff(&a, &b);
+        assert_eq!(vec![Point{x:1,y:1}, Point{x:2,y:2}], diff.points);
+        assert_eq!("#.. | #.. |\n.o. | ... |  X\n... | ..o |   X\n", diff.to_string());
+        assert!(grid_diff(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn diff_sizes() {
+        let a = Grid::from_vec(2, 1, vec![1, 10]);
+        let b = Grid::from_vec(1, 2, vec![1, 2]);
+        let diff = grid_diff(&a, &b);
+        assert_eq!(vec![Point{x:1,y:0}, Point{x:0,y:1}], diff.points);
+        assert_eq!(" 110 |  1   |    X\n     |  2   |  X\n", diff.rendering);
+    }
+}
@@ -0,0 +1,173 @@
+use std::str::FromStr;
+
+use crate::grid::Point;
+
+// y grows downwards, matching the order input lines are read in.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseDirectionError;
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    pub fn turn_right(&self) -> Self {
+        Self::ALL[(*self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(&self) -> Self {
+        Self::ALL[(*self as usize + 3) % 4]
+    }
+
+    pub fn opposite(&self) -> Self {
+        Self::ALL[(*self as usize + 2) % 4]
+    }
+
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::East => (1, 0),
+            Direction::South => (0, 1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    pub fn step(&self, p: &Point) -> Option<Point> {
+        let (dx, dy) = self.delta();
+        p.offset(dx, dy)
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseDirectionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "^" | "U" | "N" => Ok(Direction::North),
+            ">" | "R" | "E" => Ok(Direction::East),
+            "v" | "D" | "S" => Ok(Direction::South),
+            "<" | "L" | "W" => Ok(Direction::West),
+            _ => Err(ParseDirectionError),
+        }
+    }
+}
+
+impl Direction8 {
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North, Direction8::NorthEast, Direction8::East, Direction8::SouthEast,
+        Direction8::South, Direction8::SouthWest, Direction8::West, Direction8::NorthWest,
+    ];
+
+    pub fn turn_right(&self) -> Self {
+        Self::ALL[(*self as usize + 1) % 8]
+    }
+
+    pub fn turn_left(&self) -> Self {
+        Self::ALL[(*self as usize + 7) % 8]
+    }
+
+    pub fn opposite(&self) -> Self {
+        Self::ALL[(*self as usize + 4) % 8]
+    }
+
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction8::North => (0, -1),
+            Direction8::NorthEast => (1, -1),
+            Direction8::East => (1, 0),
+            Direction8::SouthEast => (1, 1),
+            Direction8::South => (0, 1),
+            Direction8::SouthWest => (-1, 1),
+            Direction8::West => (-1, 0),
+            Direction8::NorthWest => (-1, -1),
+        }
+    }
+
+    pub fn step(&self, p: &Point) -> Option<Point> {
+        let (dx, dy) = self.delta();
+        p.offset(dx, dy)
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(d: Direction) -> Self {
+        match d {
+            Direction::North => Direction8::North,
+            Direction::East => Direction8::East,
+            Direction::South => Direction8::South,
+            Direction::West => Direction8::West,
+        }
+    }
+}
+
+impl FromStr for Direction8 {
+    type Err = ParseDirectionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NE" => Ok(Direction8::NorthEast),
+            "SE" => Ok(Direction8::SouthEast),
+            "SW" => Ok(Direction8::SouthWest),
+            "NW" => Ok(Direction8::NorthWest),
+            _ => s.parse::<Direction>().map(Direction8::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turning() {
+        for d in Direction::ALL {
+            assert_eq!(d, d.turn_left().turn_right());
+            assert_eq!(d.opposite(), d.turn_right().turn_right());
+            assert_ne!(d, d.opposite());
+        }
+        assert_eq!(Direction::East, Direction::North.turn_right());
+        assert_eq!(Direction::West, Direction::North.turn_left());
+        for d in Direction8::ALL {
+            assert_eq!(d.opposite(), (0..4).fold(d, |d, _| d.turn_left()));
+            let ((dx, dy), (ox, oy)) = (d.delta(), d.opposite().delta());
+            assert_eq!((0, 0), (dx + ox, dy + oy));
+        }
+        assert_eq!(Direction8::NorthWest, Direction8::North.turn_left());
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Direction::North), "^".parse());
+        assert_eq!(Ok(Direction::South), "v".parse());
+        assert_eq!(Ok(Direction::West), "L".parse());
+        assert_eq!(Ok(Direction::East), "E".parse());
+        assert_eq!(Err(ParseDirectionError), "x".parse::<Direction>());
+        assert_eq!(Ok(Direction8::SouthWest), "SW".parse());
+        assert_eq!(Ok(Direction8::East), ">".parse());
+    }
+
+    #[test]
+    fn step() {
+        let p = Point{x:0, y:1};
+        assert_eq!(Some(Point{x:0, y:0}), Direction::North.step(&p));
+        assert_eq!(None, Direction::West.step(&p));
+        assert_eq!(Some(Point{x:1, y:2}), Direction8::SouthEast.step(&p));
+        assert_eq!(None, Direction8::NorthWest.step(&p));
+    }
+}
@@ -0,0 +1,59 @@
+use std::io;
+
+use thiserror::Error;
+
+use crate::diagnostic::{Diagnostic, ToDiagnostic};
+use crate::grid::GridError;
+
+// Every way a day can fail, so a runner can report them all alike. Each day's own parse errors
+// convert into Parse; AocError::parse does better when the input is still at hand.
+#[derive(Debug, Error)]
+pub enum AocError {
+    #[error("Read {path}: {source}")]
+    Io { path: String, source: io::Error },
+    #[error("{0}")]
+    Parse(Diagnostic),
+    #[error("Day {day} part {part} is not supported")]
+    Unsupported { day: u32, part: u32 },
+    #[error("{0}")]
+    Solver(String),
+}
+
+impl AocError {
+    pub fn io(path: &str, source: io::Error) -> Self {
+        AocError::Io { path: path.to_string(), source }
+    }
+
+    // The error underlined in the input it came from.
+    pub fn parse<E: ToDiagnostic>(error: &E, input: &str) -> Self {
+        AocError::Parse(error.to_diagnostic(input))
+    }
+}
+
+impl From<GridError> for AocError {
+    fn from(e: GridError) -> Self {
+        let line = match e {
+            GridError::Empty => 1,
+            GridError::NonAscii { at, .. } => at.y + 1,
+            GridError::RaggedLine { y, .. } => y + 1,
+        };
+        AocError::Parse(Diagnostic::at_line(line, &e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::AoCGrid;
+
+    #[test]
+    fn errors() {
+        let input = "123\n45";
+        let e = AoCGrid::try_new(input).map(|_| ()).unwrap_err();
+        assert!(AocError::parse(&e, input).to_string().ends_with("2 | 45\n  | ^^ expected 3 cells"));
+        assert_eq!("error: Not all lines are the same length: line 2 has 2 cells, expected 3\n --> line 2", AocError::from(e).to_string());
+        let missing = std::fs::read_to_string("no/such/input.txt").unwrap_err();
+        assert!(AocError::io("no/such/input.txt", missing).to_string().starts_with("Read no/such/input.txt: "));
+        assert_eq!("Day 26 part 1 is not supported", AocError::Unsupported { day: 26, part: 1 }.to_string());
+    }
+}
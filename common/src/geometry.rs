@@ -0,0 +1,474 @@
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::grid::SignedPoint;
+use crate::math::{gcd, reduce, signed_gcd};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl fmt::Display for Point3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl Point3 {
+    pub fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn component_min(&self, other: &Point3) -> Point3 {
+        Point3::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    pub fn component_max(&self, other: &Point3) -> Point3 {
+        Point3::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+
+    pub fn manhattan(&self, other: &Point3) -> u64 {
+        self.x.abs_diff(other.x)
+            .checked_add(self.y.abs_diff(other.y))
+            .and_then(|d| d.checked_add(self.z.abs_diff(other.z)))
+            .expect("manhattan distance fits in u64")
+    }
+}
+
+impl Add for Point3 {
+    type Output = Point3;
+    fn add(self, rhs: Point3) -> Self::Output {
+        Point3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Point3;
+    fn sub(self, rhs: Point3) -> Self::Output {
+        Point3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Neg for Point3 {
+    type Output = Point3;
+    fn neg(self) -> Self::Output {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<i64> for Point3 {
+    type Output = Point3;
+    fn mul(self, rhs: i64) -> Self::Output {
+        Point3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Segment {
+    pub start: SignedPoint,
+    pub end: SignedPoint,
+}
+
+// A crossing point is kept exact as numerators over a shared positive denominator;
+// an overlap runs in the same direction as the segment it was computed from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SegmentIntersection {
+    Point { x_num: i128, y_num: i128, den: i128 },
+    Overlap(Segment),
+}
+
+impl SegmentIntersection {
+    pub fn point_f64(&self) -> Option<(f64, f64)> {
+        match self {
+            SegmentIntersection::Point { x_num, y_num, den } => Some((*x_num as f64 / *den as f64, *y_num as f64 / *den as f64)),
+            SegmentIntersection::Overlap(_) => None,
+        }
+    }
+}
+
+// Vectors between i64 points need 65 bits, so products of two can overflow i128 once
+// coordinates span more than about 2^63; these give None then rather than panicking.
+fn cross(a: (i128, i128), b: (i128, i128)) -> Option<i128> {
+    a.0.checked_mul(b.1)?.checked_sub(a.1.checked_mul(b.0)?)
+}
+
+fn dot(a: (i128, i128), b: (i128, i128)) -> Option<i128> {
+    a.0.checked_mul(b.0)?.checked_add(a.1.checked_mul(b.1)?)
+}
+
+impl Segment {
+    pub fn new(start: SignedPoint, end: SignedPoint) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_horizontal(&self) -> bool { self.start.y == self.end.y }
+    pub fn is_vertical(&self) -> bool { self.start.x == self.end.x }
+    pub fn is_axis_aligned(&self) -> bool { self.is_horizontal() || self.is_vertical() }
+
+    fn vector(&self) -> (i128, i128) {
+        (self.end.x as i128 - self.start.x as i128, self.end.y as i128 - self.start.y as i128)
+    }
+
+    fn offset_to(&self, p: &SignedPoint) -> (i128, i128) {
+        (p.x as i128 - self.start.x as i128, p.y as i128 - self.start.y as i128)
+    }
+
+    fn is_parallel(&self, other: &Segment) -> Option<bool> {
+        Some(cross(self.vector(), other.vector())? == 0)
+    }
+
+    // How far along self a non-parallel other crosses it, as t / den with den > 0,
+    // or Some(None) if the lines cross off either segment.
+    fn crossing(&self, other: &Segment) -> Option<Option<(i128, i128)>> {
+        let r = self.vector();
+        let s = other.vector();
+        let qp = self.offset_to(&other.start);
+        let mut den = cross(r, s)?;
+        let mut t = cross(qp, s)?;
+        let mut u = cross(qp, r)?;
+        if den < 0 {
+            (den, t, u) = (den.checked_neg()?, t.checked_neg()?, u.checked_neg()?);
+        }
+        Some(((0..=den).contains(&t) && (0..=den).contains(&u)).then_some((t, den)))
+    }
+
+    // None if the segments are too far apart to compare in i128.
+    pub fn intersects(&self, other: &Segment) -> Option<bool> {
+        if self.is_parallel(other)? {
+            Some(self.collinear_overlap(other)?.is_some())
+        }
+        else {
+            Some(self.crossing(other)?.is_some())
+        }
+    }
+
+    // Also None if the exact crossing point doesn't fit in i128, where intersects may still answer.
+    pub fn intersection(&self, other: &Segment) -> Option<SegmentIntersection> {
+        if self.is_parallel(other)? {
+            return self.collinear_overlap(other)?;
+        }
+        let (t, den) = self.crossing(other)??;
+        // Reducing t / den first keeps hailstone-sized coordinates in range.
+        let (t, den) = reduce(t, den)?;
+        let r = self.vector();
+        let x_num = (self.start.x as i128).checked_mul(den)?.checked_add(r.0.checked_mul(t)?)?;
+        let y_num = (self.start.y as i128).checked_mul(den)?.checked_add(r.1.checked_mul(t)?)?;
+        let g = signed_gcd(signed_gcd(x_num, y_num)?, den)?;
+        Some(SegmentIntersection::Point { x_num: x_num / g, y_num: y_num / g, den: den / g })
+    }
+
+    // Outer None on overflow, like crossing.
+    fn collinear_overlap(&self, other: &Segment) -> Option<Option<SegmentIntersection>> {
+        let point = |p: SignedPoint| SegmentIntersection::Point { x_num: p.x as i128, y_num: p.y as i128, den: 1 };
+        let r = self.vector();
+        if r == (0, 0) {
+            return Some(other.contains(&self.start)?.then(|| point(self.start)));
+        }
+        if other.vector() == (0, 0) {
+            return Some(self.contains(&other.start)?.then(|| point(other.start)));
+        }
+        if cross(self.offset_to(&other.start), r)? != 0 {
+            // Parallel but on different lines
+            return Some(None);
+        }
+        let keyed = |p: SignedPoint| Some((dot(self.offset_to(&p), r)?, p));
+        let mut mine = [keyed(self.start)?, keyed(self.end)?];
+        let mut theirs = [keyed(other.start)?, keyed(other.end)?];
+        mine.sort_by_key(|k| k.0);
+        theirs.sort_by_key(|k| k.0);
+        let lo = if mine[0].0 >= theirs[0].0 { mine[0] } else { theirs[0] };
+        let hi = if mine[1].0 <= theirs[1].0 { mine[1] } else { theirs[1] };
+        if lo.0 > hi.0 {
+            Some(None)
+        }
+        else if lo.1 == hi.1 {
+            Some(Some(point(lo.1)))
+        }
+        else {
+            Some(Some(SegmentIntersection::Overlap(Segment::new(lo.1, hi.1))))
+        }
+    }
+
+    // None if p is too far from the segment to compare in i128.
+    pub fn contains(&self, p: &SignedPoint) -> Option<bool> {
+        let r = self.vector();
+        let d = self.offset_to(p);
+        if r == (0, 0) {
+            return Some(d == (0, 0));
+        }
+        if cross(d, r)? != 0 {
+            return Some(false);
+        }
+        Some((0..=dot(r, r)?).contains(&dot(d, r)?))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Segment3 {
+    pub start: Point3,
+    pub end: Point3,
+}
+
+impl Segment3 {
+    pub fn new(start: Point3, end: Point3) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_axis_aligned(&self) -> bool {
+        let d = self.end - self.start;
+        [d.x, d.y, d.z].iter().filter(|c| **c != 0).count() <= 1
+    }
+
+    // Projection onto the xy plane, e.g. the footprint of a falling brick.
+    pub fn xy(&self) -> Segment {
+        Segment::new(SignedPoint { x: self.start.x, y: self.start.y }, SignedPoint { x: self.end.x, y: self.end.y })
+    }
+
+    // Only defined for axis-aligned segments lying on the same line.
+    pub fn overlap(&self, other: &Segment3) -> Option<Segment3> {
+        if !self.is_axis_aligned() || !other.is_axis_aligned() {
+            return None;
+        }
+        let lo = self.start.component_min(&self.end).component_max(&other.start.component_min(&other.end));
+        let hi = self.start.component_max(&self.end).component_min(&other.start.component_max(&other.end));
+        if lo.x <= hi.x && lo.y <= hi.y && lo.z <= hi.z {
+            Some(Segment3::new(lo, hi))
+        }
+        else {
+            None
+        }
+    }
+}
+
+// Vertices in order; the closing edge from the last vertex back to the first is implied.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polygon {
+    pub vertices: Vec<SignedPoint>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<SignedPoint>) -> Self {
+        Self { vertices }
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.vertices.iter().zip(self.vertices.iter().cycle().skip(1)).map(|(a, b)| Segment::new(*a, *b))
+    }
+
+    // Positive for anticlockwise vertices in a y-up frame, i.e. clockwise as drawn on a grid.
+    pub fn double_signed_area(&self) -> i128 {
+        double_signed_area(&self.vertices)
+    }
+
+    pub fn signed_area(&self) -> f64 {
+        self.double_signed_area() as f64 / 2.0
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges().map(|e| {
+            // In f64 throughout, as squaring a 65-bit edge vector can overflow i128.
+            let (dx, dy) = e.vector();
+            (dx as f64).hypot(dy as f64)
+        }).sum()
+    }
+
+    pub fn boundary_points(&self) -> i128 {
+        self.edges().map(|e| {
+            // A repeated vertex adds nothing, as gcd(0, 0) is 0.
+            let (dx, dy) = e.vector();
+            gcd(dx.unsigned_abs(), dy.unsigned_abs()) as i128
+        }).sum()
+    }
+
+    pub fn interior_points(&self) -> i128 {
+        interior_lattice_points(self.double_signed_area().abs(), self.boundary_points())
+    }
+
+    pub fn enclosed_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    // Points on the boundary count as contained. None if p is too far out to compare in i128.
+    pub fn contains(&self, p: &SignedPoint) -> Option<bool> {
+        let mut inside = false;
+        for e in self.edges() {
+            if e.contains(p)? {
+                return Some(true);
+            }
+            let (a, b) = (e.start, e.end);
+            if (a.y > p.y) != (b.y > p.y) {
+                // Is p left of the edge's crossing with the horizontal line through p?
+                let lhs = (p.x as i128 - a.x as i128).checked_mul(b.y as i128 - a.y as i128)?;
+                let rhs = (p.y as i128 - a.y as i128).checked_mul(b.x as i128 - a.x as i128)?;
+                if (b.y > a.y && lhs < rhs) || (b.y < a.y && lhs > rhs) {
+                    inside = !inside;
+                }
+            }
+        }
+        Some(inside)
+    }
+}
+
+// The shoelace formula, over vertices in order with the closing edge implied.
+fn double_signed_area(points: &[SignedPoint]) -> i128 {
+    points.iter().zip(points.iter().cycle().skip(1)).map(|(a, b)| a.x as i128 * b.y as i128 - b.x as i128 * a.y as i128).sum()
+}
+
+// Lattice polygons can have half-unit areas, so this stays exact where polygon_area can't.
+pub fn double_polygon_area(points: &[SignedPoint]) -> i128 {
+    double_signed_area(points).abs()
+}
+
+// Whichever way round the vertices go.
+pub fn polygon_area(points: &[SignedPoint]) -> f64 {
+    double_polygon_area(points) as f64 / 2.0
+}
+
+// Pick's theorem, A = i + b/2 - 1, solved for i. Takes the doubled area from
+// double_polygon_area and the lattice points on the boundary.
+pub fn interior_lattice_points(double_area: i128, boundary: i128) -> i128 {
+    (double_area - boundary + 2) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn point3() {
+        let a = Point3::new(1, -2, 3);
+        let b = Point3::new(-4, 5, 0);
+        assert_eq!(Point3::new(-3, 3, 3), a + b);
+        assert_eq!(Point3::new(5, -7, 3), a - b);
+        assert_eq!(Point3::new(-1, 2, -3), -a);
+        assert_eq!(Point3::new(2, -4, 6), a * 2);
+        assert_eq!(Point3::new(-4, -2, 0), a.component_min(&b));
+        assert_eq!(Point3::new(1, 5, 3), a.component_max(&b));
+        assert_eq!(15, a.manhattan(&b));
+        assert_eq!("(1, -2, 3)", a.to_string());
+        let set: HashSet<Point3> = [a, b, a + Point3::default()].into_iter().collect();
+        assert_eq!(2, set.len());
+    }
+
+    fn seg(x0: i64, y0: i64, x1: i64, y1: i64) -> Segment {
+        Segment::new(SignedPoint{x:x0, y:y0}, SignedPoint{x:x1, y:y1})
+    }
+
+    #[test]
+    fn segment_crossing() {
+        assert_eq!(Some(SegmentIntersection::Point{x_num:2, y_num:2, den:1}), seg(0,0,4,4).intersection(&seg(0,4,4,0)));
+        assert_eq!(Some(SegmentIntersection::Point{x_num:1, y_num:1, den:2}), seg(0,0,1,1).intersection(&seg(0,1,1,0)));
+        assert_eq!(Some((0.5, 0.5)), seg(0,0,1,1).intersection(&seg(0,1,1,0)).unwrap().point_f64());
+        assert_eq!(Some(SegmentIntersection::Point{x_num:3, y_num:0, den:1}), seg(0,0,3,0).intersection(&seg(3,-2,3,5)));
+        assert_eq!(None, seg(0,0,2,2).intersection(&seg(3,0,4,-1)));
+        assert_eq!(None, seg(0,0,4,0).intersection(&seg(0,1,4,1)));
+        assert_eq!(Some(true), seg(-5,-5,5,5).intersects(&seg(-5,5,5,-5)));
+    }
+
+    #[test]
+    fn hailstone_sized_crossing() {
+        // Two day 24 hailstones' paths over a trillion nanoseconds.
+        let a = seg(262130794315133, 305267994111063, 319130794315133, 53267994111063);
+        let b = seg(290550702673836, 186986670515285, 275550702673836, 37986670515285);
+        let expected = SegmentIntersection::Point{x_num:1186544017331494026, y_num:744113323160151705, den:4091};
+        assert_eq!(Some(expected), a.intersection(&b));
+        assert_eq!(Some(true), a.intersects(&b));
+    }
+
+    #[test]
+    fn extreme_coordinates() {
+        let (lo, hi) = (i64::MIN, i64::MAX);
+        // Both diagonals of the whole i64 plane: their cross product needs about 2^130.
+        assert_eq!(None, seg(lo,lo,hi,hi).intersects(&seg(lo,hi,hi,lo)));
+        assert_eq!(None, seg(lo,lo,hi,hi).intersection(&seg(lo,hi,hi,lo)));
+        assert_eq!(None, seg(lo,lo,hi,hi).contains(&SignedPoint{x:0, y:0}));
+        assert_eq!(None, seg(lo,0,hi,0).intersects(&seg(0,lo,0,hi)));
+        // Within half the range every product fits.
+        let (lo2, hi2) = (lo >> 1, hi >> 1);
+        assert_eq!(Some(true), seg(lo2,lo2,hi2,hi2).intersects(&seg(lo2,hi2,hi2,lo2)));
+        assert_eq!(Some(SegmentIntersection::Point{x_num:-1, y_num:-1, den:2}), seg(lo2,lo2,hi2,hi2).intersection(&seg(lo2,hi2,hi2,lo2)));
+        assert_eq!(Some(true), seg(lo2,lo2,hi2,hi2).contains(&SignedPoint{x:0, y:0}));
+        let square = Polygon::new(vec![SignedPoint{x:lo, y:lo}, SignedPoint{x:hi, y:lo}, SignedPoint{x:hi, y:hi}, SignedPoint{x:lo, y:hi}]);
+        let side = hi as f64 - lo as f64;
+        assert_eq!(4.0 * side, square.perimeter());
+        assert_eq!(Some(true), square.contains(&SignedPoint{x:0, y:0}));
+        assert_eq!(Some(true), Polygon::new(vec![SignedPoint{x:lo2, y:lo2}, SignedPoint{x:hi2, y:lo2}, SignedPoint{x:hi2, y:hi2}]).contains(&SignedPoint{x:0, y:-1}));
+        let diamond = Polygon::new(vec![SignedPoint{x:lo, y:0}, SignedPoint{x:0, y:lo}, SignedPoint{x:hi, y:0}, SignedPoint{x:0, y:hi}]);
+        assert!((diamond.perimeter() - 2.0 * 2f64.sqrt() * side).abs() <= diamond.perimeter() * 1e-12);
+    }
+
+    #[test]
+    fn segment_overlap() {
+        assert!(seg(0,0,5,0).is_horizontal() && seg(1,2,1,7).is_vertical() && !seg(0,0,1,1).is_axis_aligned());
+        assert_eq!(Some(SegmentIntersection::Overlap(seg(2,0,5,0))), seg(0,0,5,0).intersection(&seg(7,0,2,0)));
+        assert_eq!(Some(SegmentIntersection::Overlap(seg(1,4,1,3))), seg(1,4,1,0).intersection(&seg(1,3,1,9)));
+        assert_eq!(Some(SegmentIntersection::Point{x_num:5, y_num:0, den:1}), seg(0,0,5,0).intersection(&seg(5,0,8,0)));
+        assert_eq!(None, seg(0,0,5,0).intersection(&seg(6,0,8,0)));
+        assert_eq!(Some(SegmentIntersection::Overlap(seg(1,1,2,2))), seg(0,0,2,2).intersection(&seg(1,1,3,3)));
+        assert_eq!(Some(SegmentIntersection::Point{x_num:2, y_num:0, den:1}), seg(2,0,2,0).intersection(&seg(0,0,5,0)));
+        assert_eq!(None, seg(2,1,2,1).intersection(&seg(0,0,5,0)));
+        assert_eq!(Some(true), seg(0,0,4,2).contains(&SignedPoint{x:2, y:1}));
+        assert_eq!(Some(false), seg(0,0,4,2).contains(&SignedPoint{x:6, y:3}));
+    }
+
+    #[test]
+    fn polygon() {
+        let pts = |v: &[(i64, i64)]| Polygon::new(v.iter().map(|(x, y)| SignedPoint{x:*x, y:*y}).collect());
+        let square = pts(&[(0,0), (4,0), (4,4), (0,4)]);
+        assert_eq!(32, square.double_signed_area());
+        assert_eq!(16.0, square.signed_area());
+        assert_eq!(-16.0, pts(&[(0,0), (0,4), (4,4), (4,0)]).signed_area());
+        assert_eq!(16.0, square.perimeter());
+        assert_eq!(16, square.boundary_points());
+        assert_eq!(16, pts(&[(0,0), (4,0), (4,0), (4,4), (0,4)]).boundary_points());
+        assert_eq!(9, square.interior_points());
+        assert_eq!(25, square.enclosed_points());
+        let triangle = pts(&[(0,0), (3,0), (0,4)]);
+        assert_eq!(12.0, triangle.perimeter());
+        assert_eq!(6.0, triangle.signed_area());
+        assert_eq!(3, triangle.interior_points());
+        assert_eq!(Some(true), square.contains(&SignedPoint{x:2, y:2}));
+        assert_eq!(Some(true), square.contains(&SignedPoint{x:4, y:1}));
+        assert_eq!(Some(false), square.contains(&SignedPoint{x:5, y:2}));
+        let u = pts(&[(0,0), (6,0), (6,6), (4,6), (4,2), (2,2), (2,6), (0,6)]);
+        assert_eq!(Some(true), u.contains(&SignedPoint{x:1, y:5}));
+        assert_eq!(Some(false), u.contains(&SignedPoint{x:3, y:4}));
+        assert_eq!(Some(true), u.contains(&SignedPoint{x:3, y:1}));
+        assert_eq!(Some(false), triangle.contains(&SignedPoint{x:2, y:2}));
+        assert_eq!(Some(true), triangle.contains(&SignedPoint{x:1, y:1}));
+    }
+
+    #[test]
+    fn shoelace_and_pick() {
+        let pts = |v: &[(i64, i64)]| v.iter().map(|(x, y)| SignedPoint{x:*x, y:*y}).collect::<Vec<_>>();
+        // The example dig plan's trench, corner to corner.
+        let trench = pts(&[(0,0), (6,0), (6,5), (4,5), (4,7), (6,7), (6,9), (1,9), (1,7), (0,7), (0,5), (2,5), (2,2), (0,2)]);
+        assert_eq!(42.0, polygon_area(&trench));
+        let boundary = Polygon::new(trench.clone()).boundary_points();
+        assert_eq!(38, boundary);
+        assert_eq!(24, interior_lattice_points(double_polygon_area(&trench), boundary));
+        assert_eq!(62, interior_lattice_points(double_polygon_area(&trench), boundary) + boundary);
+        let triangle = pts(&[(0,0), (0,1), (1,0)]);
+        assert_eq!((1, 0.5), (double_polygon_area(&triangle), polygon_area(&triangle)));
+        assert_eq!(0, interior_lattice_points(1, 3));
+        assert_eq!(0.0, polygon_area(&[]));
+    }
+
+    #[test]
+    fn segment3() {
+        let s = |a: (i64, i64, i64), b: (i64, i64, i64)| Segment3::new(Point3::new(a.0, a.1, a.2), Point3::new(b.0, b.1, b.2));
+        assert!(s((1,0,1), (1,2,1)).is_axis_aligned());
+        assert!(!s((1,0,1), (1,2,2)).is_axis_aligned());
+        assert_eq!(Some(s((1,1,1), (1,2,1))), s((1,0,1), (1,2,1)).overlap(&s((1,1,1), (1,5,1))));
+        assert_eq!(None, s((1,0,1), (1,2,1)).overlap(&s((2,0,1), (2,2,1))));
+        assert_eq!(Some(true), s((1,0,1), (1,2,1)).xy().intersects(&s((0,0,2), (2,0,2)).xy()));
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, Range, Sub};
+
+// Nodes are numbered from 0 in the order their keys were first seen.
+pub type NodeId = usize;
+
+// Adjacency lists, with nodes interned by key so puzzles can add edges by name. An undirected
+// edge is stored once from each end, so it shows up in both nodes' neighbours.
+#[derive(Clone, Debug)]
+pub struct Graph<N, E = ()> {
+    keys: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    edges: Vec<Vec<(NodeId, E)>>,
+    directed: bool,
+}
+
+impl<N: Clone + Eq + Hash, E: Clone> Graph<N, E> {
+    pub fn directed() -> Self {
+        Self { keys: Vec::new(), ids: HashMap::new(), edges: Vec::new(), directed: true }
+    }
+
+    pub fn undirected() -> Self {
+        Self { directed: false, ..Self::directed() }
+    }
+
+    pub fn is_directed(&self) -> bool { self.directed }
+    pub fn len(&self) -> usize { self.keys.len() }
+    pub fn is_empty(&self) -> bool { self.keys.is_empty() }
+    pub fn nodes(&self) -> Range<NodeId> { 0..self.keys.len() }
+    pub fn id(&self, key: &N) -> Option<NodeId> { self.ids.get(key).copied() }
+    pub fn key(&self, id: NodeId) -> &N { &self.keys[id] }
+
+    // The id for key, adding it as a node with no edges if it's new.
+    pub fn node(&mut self, key: N) -> NodeId {
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = self.keys.len();
+        self.keys.push(key.clone());
+        self.ids.insert(key, id);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: E) -> (NodeId, NodeId) {
+        let (from, to) = (self.node(from), self.node(to));
+        self.add_edge_by_id(from, to, weight);
+        (from, to)
+    }
+
+    // An undirected loop from a node to itself is only stored once.
+    pub fn add_edge_by_id(&mut self, from: NodeId, to: NodeId, weight: E) {
+        if !self.directed && from != to {
+            self.edges[to].push((from, weight.clone()));
+        }
+        self.edges[from].push((to, weight));
+    }
+
+    pub fn edges_from(&self, id: NodeId) -> &[(NodeId, E)] {
+        &self.edges[id]
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges[id].iter().map(|(to, _)| *to)
+    }
+
+    // Every edge once, as (from, to, weight); undirected edges come out with from <= to.
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &E)> + '_ {
+        self.edges.iter().enumerate()
+            .flat_map(|(from, edges)| edges.iter().map(move |(to, weight)| (from, *to, weight)))
+            .filter(|(from, to, _)| self.directed || from <= to)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges().count()
+    }
+
+    // Kahn's algorithm: every node comes before the nodes its edges lead to, with ties going to
+    // the earliest added. Any edge of an undirected graph goes both ways, so is reported as a
+    // cycle of two, or of one for a loop.
+    pub fn topological_sort(&self) -> Result<Vec<NodeId>, CycleError> {
+        if !self.directed {
+            return match self.edges().next() {
+                Some((from, to, _)) => Err(CycleError { cycle: if from == to { vec![from] } else { vec![from, to] } }),
+                None => Ok(self.nodes().collect()),
+            };
+        }
+        let mut in_degrees: Vec<usize> = vec![0; self.len()];
+        for (_, to, _) in self.edges() {
+            in_degrees[to] += 1;
+        }
+        let mut ready: VecDeque<NodeId> = self.nodes().filter(|n| in_degrees[*n] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(node) = ready.pop_front() {
+            order.push(node);
+            for next in self.neighbors(node) {
+                in_degrees[next] -= 1;
+                if in_degrees[next] == 0 {
+                    ready.push_back(next);
+                }
+            }
+        }
+        if order.len() == self.len() {
+            return Ok(order);
+        }
+        // Every node left over still has an edge in from another left over, so walking those
+        // edges backwards must come round to a node already passed.
+        let mut predecessors = vec![None; self.len()];
+        for (from, to, _) in self.edges().filter(|(from, to, _)| in_degrees[*from] > 0 && in_degrees[*to] > 0) {
+            predecessors[to] = Some(from);
+        }
+        let mut walked: Vec<NodeId> = vec![self.nodes().find(|n| in_degrees[*n] > 0).expect("A node left over")];
+        loop {
+            let previous = predecessors[*walked.last().expect("Walk is never empty")].expect("Left over nodes have predecessors");
+            if let Some(at) = walked.iter().position(|n| *n == previous) {
+                let mut cycle = walked.split_off(at);
+                cycle.reverse();
+                return Err(CycleError { cycle });
+            }
+            walked.push(previous);
+        }
+    }
+
+    // Tarjan's algorithm, without recursion so long chains can't overflow the stack. A
+    // component only comes out once every component it has edges into has, so the list is in
+    // reverse topological order of the condensed graph. Nodes within a component are in the
+    // order the search reached them.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        const UNVISITED: usize = usize::MAX;
+        let mut index = vec![UNVISITED; self.len()];
+        let mut low_link = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
+        for root in self.nodes() {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            // Each node being explored, with how many of its edges have been followed.
+            let mut calls = vec![(root, 0)];
+            while let Some(&(node, edge)) = calls.last() {
+                if edge == 0 {
+                    (index[node], low_link[node], on_stack[node]) = (next_index, next_index, true);
+                    next_index += 1;
+                    stack.push(node);
+                }
+                if let Some(&(next, _)) = self.edges[node].get(edge) {
+                    calls.last_mut().expect("Just looked at it").1 += 1;
+                    if index[next] == UNVISITED {
+                        calls.push((next, 0));
+                    }
+                    else if on_stack[next] {
+                        low_link[node] = low_link[node].min(index[next]);
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+                if low_link[node] == index[node] {
+                    let at = stack.iter().rposition(|n| *n == node).expect("Node is on the stack");
+                    let component = stack.split_off(at);
+                    for n in &component {
+                        on_stack[*n] = false;
+                    }
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    // Stoer-Wagner: the lightest set of edges whose removal splits the graph in two, read as
+    // undirected with each edge weighing weight(edge). Returns the cut's weight and the nodes on
+    // the same side as node 0, the rest being on the other. None for fewer than two nodes.
+    pub fn min_cut<W, F>(&self, weight: F) -> Option<(W, Vec<NodeId>)>
+        where W: Copy + Ord + Add<Output = W> + Default,
+              F: Fn(&E) -> W
+    {
+        if self.len() < 2 {
+            return None;
+        }
+        // Nodes get merged as the phases go; a merged node keeps the lower id.
+        let mut adjacency: Vec<HashMap<NodeId, W>> = vec![HashMap::new(); self.len()];
+        for (from, to, e) in self.edges().filter(|(from, to, _)| from != to) {
+            let w = weight(e);
+            for (a, b) in [(from, to), (to, from)] {
+                let total = adjacency[a].entry(b).or_default();
+                *total = *total + w;
+            }
+        }
+        let mut members: Vec<Vec<NodeId>> = self.nodes().map(|n| vec![n]).collect();
+        let mut active = vec![true; self.len()];
+        let mut best: Option<(W, Vec<NodeId>)> = None;
+        for remaining in (2..=self.len()).rev() {
+            // Add nodes most tightly connected to those already added first; the last two added
+            // are then separated by no cut lighter than the last one's connection to the rest.
+            let mut added = vec![false; self.len()];
+            let mut connectivity = vec![W::default(); self.len()];
+            let mut heap: BinaryHeap<(W, NodeId)> = BinaryHeap::new();
+            let (mut previous, mut last) = (None, None);
+            for _ in 0..remaining {
+                let node = loop {
+                    match heap.pop() {
+                        Some((w, node)) if !added[node] && w == connectivity[node] => break node,
+                        Some(_) => continue,
+                        // What's left isn't connected to what's been added.
+                        None => break (0..self.len()).find(|n| active[*n] && !added[*n]).expect("An active node left"),
+                    }
+                };
+                added[node] = true;
+                (previous, last) = (last, Some(node));
+                for (&next, &w) in &adjacency[node] {
+                    if !added[next] {
+                        connectivity[next] = connectivity[next] + w;
+                        heap.push((connectivity[next], next));
+                    }
+                }
+            }
+            let (previous, last) = (previous.expect("At least two nodes"), last.expect("At least two nodes"));
+            if best.as_ref().is_none_or(|(w, _)| connectivity[last] < *w) {
+                best = Some((connectivity[last], members[last].clone()));
+            }
+            // Merge last into previous.
+            for (next, w) in std::mem::take(&mut adjacency[last]) {
+                adjacency[next].remove(&last);
+                if next != previous {
+                    for (a, b) in [(previous, next), (next, previous)] {
+                        let total = adjacency[a].entry(b).or_default();
+                        *total = *total + w;
+                    }
+                }
+            }
+            let merged = std::mem::take(&mut members[last]);
+            members[previous].extend(merged);
+            active[last] = false;
+        }
+        best.map(|(w, side)| {
+            let mut on_side = vec![false; self.len()];
+            for n in side {
+                on_side[n] = true;
+            }
+            (w, self.nodes().filter(|n| on_side[*n] == on_side[0]).collect())
+        })
+    }
+
+    // The heaviest path from start to end of a directed acyclic graph, counting edge_weight for
+    // every edge taken and node_weight for every node on it, start and end included. Ok(None)
+    // if end can't be reached.
+    pub fn longest_path_dag<W, FE, FN>(&self, start: NodeId, end: NodeId, edge_weight: FE, node_weight: FN) -> Result<Option<(W, Vec<NodeId>)>, CycleError>
+        where W: Copy + Ord + Add<Output = W>,
+              FE: Fn(&E) -> W,
+              FN: Fn(NodeId) -> W
+    {
+        let order = self.topological_sort()?;
+        // The heaviest way found to each node, and the node it came from.
+        let mut best: Vec<Option<(W, Option<NodeId>)>> = vec![None; self.len()];
+        best[start] = Some((node_weight(start), None));
+        for node in order {
+            let Some((weight, _)) = best[node] else { continue };
+            for (next, e) in &self.edges[node] {
+                let candidate = weight + edge_weight(e) + node_weight(*next);
+                if best[*next].is_none_or(|(b, _)| candidate > b) {
+                    best[*next] = Some((candidate, Some(node)));
+                }
+            }
+        }
+        Ok(best[end].map(|(weight, _)| {
+            let mut path = vec![end];
+            while let Some((_, Some(previous))) = best[*path.last().expect("Path is never empty")] {
+                path.push(previous);
+            }
+            path.reverse();
+            (weight, path)
+        }))
+    }
+
+    // The same for any graph, cycles and all, over paths that visit no node twice. Tries every
+    // path, abandoning any that couldn't beat the best so far even if it went on to pick up
+    // every node left, so it's only for small graphs. Weights mustn't be negative.
+    pub fn longest_simple_path<W, FE, FN>(&self, start: NodeId, end: NodeId, edge_weight: FE, node_weight: FN) -> Option<(W, Vec<NodeId>)>
+        where W: Copy + Ord + Add<Output = W> + Sub<Output = W> + Default,
+              FE: Fn(&E) -> W,
+              FN: Fn(NodeId) -> W
+    {
+        // The most stepping onto a node and then off it again could add.
+        let potential: Vec<W> = self.nodes()
+            .map(|n| node_weight(n) + self.edges[n].iter().map(|(_, e)| edge_weight(e)).max().unwrap_or_default())
+            .collect();
+        let mut remaining = self.nodes().filter(|n| *n != start).fold(W::default(), |total, n| total + potential[n]);
+        let mut on_path = vec![false; self.len()];
+        on_path[start] = true;
+        let mut best: Option<(W, Vec<NodeId>)> = None;
+        // The path so far: each node with how many of its edges have been tried, and the
+        // weight of the path up to it.
+        let mut path: Vec<(NodeId, usize, W)> = vec![(start, 0, node_weight(start))];
+        while let Some(&(node, edge, weight)) = path.last() {
+            let next = if node == end { None } else { self.edges[node].get(edge) };
+            let Some((next, e)) = next else {
+                if node == end && best.as_ref().is_none_or(|(b, _)| weight > *b) {
+                    best = Some((weight, path.iter().map(|(n, _, _)| *n).collect()));
+                }
+                path.pop();
+                on_path[node] = false;
+                if node != start {
+                    remaining = remaining + potential[node];
+                }
+                continue;
+            };
+            path.last_mut().expect("Just looked at it").1 += 1;
+            if on_path[*next] {
+                continue;
+            }
+            let weight = weight + edge_weight(e) + node_weight(*next);
+            let bound = weight + remaining;
+            if best.as_ref().is_some_and(|(b, _)| bound <= *b) {
+                continue;
+            }
+            on_path[*next] = true;
+            remaining = remaining - potential[*next];
+            path.push((*next, 0, weight));
+        }
+        best
+    }
+}
+
+// The nodes of one cycle, in order, each with an edge to the next and the last back to the first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CycleError {
+    pub cycle: Vec<NodeId>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the graph has a cycle through nodes {:?}", self.cycle)
+    }
+}
+
+impl Error for CycleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_network() {
+        let mut g: Graph<&str> = Graph::directed();
+        for (from, to) in [("broadcaster", "a"), ("broadcaster", "b"), ("a", "b"), ("b", "con"), ("con", "output")] {
+            g.add_edge(from, to, ());
+        }
+        assert_eq!(5, g.len());
+        assert_eq!(5, g.edge_count());
+        let b = g.id(&"b").unwrap();
+        assert_eq!("b", *g.key(b));
+        assert_eq!(vec!["con"], g.neighbors(b).map(|n| *g.key(n)).collect::<Vec<_>>());
+        assert_eq!(b, g.node("b"));
+        assert_eq!(None, g.id(&"rx"));
+        let rx = g.node("rx");
+        assert_eq!((6, 0), (g.len(), g.neighbors(rx).count()));
+    }
+
+    #[test]
+    fn topological_order() {
+        let mut g: Graph<&str> = Graph::directed();
+        for (from, to) in [("humidity", "location"), ("seed", "soil"), ("soil", "water"), ("water", "humidity"), ("seed", "water")] {
+            g.add_edge(from, to, ());
+        }
+        let order: Vec<&str> = g.topological_sort().unwrap().into_iter().map(|n| *g.key(n)).collect();
+        assert_eq!(vec!["seed", "soil", "water", "humidity", "location"], order);
+        let (soil, water, humidity) = (g.id(&"soil").unwrap(), g.id(&"water").unwrap(), g.id(&"humidity").unwrap());
+        g.add_edge("humidity", "soil", ());
+        let error = g.topological_sort().unwrap_err();
+        assert_eq!(vec![soil, water, humidity], error.cycle);
+        let mut looped: Graph<u8> = Graph::directed();
+        looped.add_edge(1, 1, ());
+        assert_eq!(Err(CycleError { cycle: vec![0] }), looped.topological_sort());
+        assert_eq!(Ok(vec![]), Graph::<u8>::directed().topological_sort());
+        let mut undirected: Graph<u8> = Graph::undirected();
+        undirected.node(7);
+        assert_eq!(Ok(vec![0]), undirected.topological_sort());
+        undirected.add_edge(8, 9, ());
+        assert_eq!(Err(CycleError { cycle: vec![1, 2] }), undirected.topological_sort());
+    }
+
+    #[test]
+    fn components() {
+        let mut g: Graph<char> = Graph::directed();
+        for (from, to) in [('a', 'b'), ('b', 'c'), ('c', 'a'), ('c', 'd'), ('d', 'e'), ('e', 'd'), ('e', 'f'), ('g', 'g'), ('g', 'a')] {
+            g.add_edge(from, to, ());
+        }
+        let names = |components: Vec<Vec<NodeId>>| components.into_iter().map(|c| c.into_iter().map(|n| *g.key(n)).collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(vec!["f", "de", "abc", "g"], names(g.strongly_connected_components()));
+        // A path long enough to overflow the stack if the search recursed.
+        let mut chain: Graph<u32> = Graph::directed();
+        for n in 0..200_000 {
+            chain.add_edge(n, n + 1, ());
+        }
+        chain.add_edge(200_000, 0, ());
+        assert_eq!(vec![200_001], chain.strongly_connected_components().iter().map(|c| c.len()).collect::<Vec<_>>());
+        assert!(Graph::<u8>::directed().strongly_connected_components().is_empty());
+    }
+
+    #[test]
+    fn minimum_cut() {
+        let wiring = "jqt: rhn xhk nvd\nrsh: frs pzl lsr\nxhk: hfx\ncmg: qnr nvd lhk bvb\nrhn: xhk bvb hfx\nbvb: xhk hfx\npzl: lsr hfx nvd\nqnr: nvd\nntq: jqt hfx bvb xhk\nnvd: lhk\nlsr: lhk\nrzs: qnr cmg lsr rsh\nfrs: qnr lhk lsr";
+        let mut g: Graph<&str> = Graph::undirected();
+        for line in wiring.lines() {
+            let (from, tos) = line.split_once(": ").unwrap();
+            for to in tos.split(' ') {
+                g.add_edge(from, to, ());
+            }
+        }
+        let (cut, side) = g.min_cut(|_| 1u32).unwrap();
+        assert_eq!(3, cut);
+        assert_eq!(54, side.len() * (g.len() - side.len()));
+        let mut weighted: Graph<char, u64> = Graph::undirected();
+        for (from, to, w) in [('a', 'b', 2), ('b', 'c', 5), ('c', 'a', 1), ('c', 'd', 4), ('b', 'd', 4), ('a', 'a', 9)] {
+            weighted.add_edge(from, to, w);
+        }
+        assert_eq!(Some((3, vec![0])), weighted.min_cut(|w| *w));
+        weighted.add_edge('x', 'y', 7);
+        assert_eq!(0, weighted.min_cut(|w| *w).unwrap().0);
+        let mut single: Graph<char> = Graph::undirected();
+        single.node('a');
+        assert_eq!(None, single.min_cut(|_| 1));
+    }
+
+    #[test]
+    fn longest_paths() {
+        let mut dag: Graph<char, u32> = Graph::directed();
+        for (from, to, w) in [('s', 'a', 3), ('s', 'b', 2), ('a', 'c', 4), ('b', 'c', 1), ('b', 'd', 9), ('c', 'e', 2), ('d', 'e', 1), ('a', 'd', 1)] {
+            dag.add_edge(from, to, w);
+        }
+        let (s, e) = (dag.id(&'s').unwrap(), dag.id(&'e').unwrap());
+        let names = |(w, path): (u32, Vec<NodeId>)| (w, path.into_iter().map(|n| *dag.key(n)).collect::<String>());
+        assert_eq!(Some((12, "sbde".to_string())), dag.longest_path_dag(s, e, |w| *w, |_| 0).unwrap().map(names));
+        // Enough weight on c makes the way through it win.
+        let c = dag.id(&'c').unwrap();
+        assert_eq!(Some((19, "sace".to_string())), dag.longest_path_dag(s, e, |w| *w, |n| if n == c { 10 } else { 0 }).unwrap().map(names));
+        assert_eq!(Some((19, "sace".to_string())), dag.longest_simple_path(s, e, |w| *w, |n| if n == c { 10 } else { 0 }).map(names));
+        assert_eq!(Some((12, "sbde".to_string())), dag.longest_simple_path(s, e, |w| *w, |_| 0).map(names));
+        assert_eq!(None, dag.longest_path_dag(e, s, |w| *w, |_| 0).unwrap());
+        assert_eq!(Some((0, "s".to_string())), dag.longest_path_dag(s, s, |w| *w, |_| 0).unwrap().map(names));
+        dag.add_edge('e', 's', 1);
+        assert!(dag.longest_path_dag(s, e, |w| *w, |_| 0).is_err());
+
+        // A ring with a shortcut that isn't worth taking.
+        let mut ring: Graph<u8, u32> = Graph::undirected();
+        for (from, to, w) in [(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 4, 1), (4, 5, 1), (5, 0, 1), (0, 3, 2)] {
+            ring.add_edge(from, to, w);
+        }
+        let best = ring.longest_simple_path(0, 3, |w| *w, |_| 0).unwrap();
+        assert_eq!((3, 4), (best.0, best.1.len()));
+        assert_eq!(Some((5, vec![0, 5, 4, 3, 2, 1])), ring.longest_simple_path(0, 1, |w| *w, |_| 0));
+        let mut apart: Graph<u8, u32> = Graph::undirected();
+        apart.add_edge(0, 1, 1);
+        apart.node(2);
+        assert_eq!(None, apart.longest_simple_path(0, 2, |w| *w, |_| 0));
+    }
+
+    #[test]
+    fn undirected() {
+        let mut g: Graph<char, u32> = Graph::undirected();
+        let (a, b) = g.add_edge('a', 'b', 3);
+        g.add_edge('b', 'c', 4);
+        g.add_edge('c', 'c', 1);
+        assert!(!g.is_directed());
+        assert_eq!(&[(b, 3)], g.edges_from(a));
+        assert_eq!(vec![a, 2], g.neighbors(b).collect::<Vec<_>>());
+        assert_eq!(vec![(0, 1, &3), (1, 2, &4), (2, 2, &1)], g.edges().collect::<Vec<_>>());
+        assert_eq!(0..3, g.nodes());
+    }
+}
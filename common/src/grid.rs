@@ -0,0 +1,1025 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+use std::vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, rhs: Point) -> Self::Output {
+        Point { x: self.x + rhs.x, y: self.y + rhs.y }
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, rhs: Point) -> Self::Output {
+        Point { x: self.x - rhs.x, y: self.y - rhs.y }
+    }
+}
+
+impl Add<(isize, isize)> for Point {
+    type Output = Point;
+    fn add(self, (dx, dy): (isize, isize)) -> Self::Output {
+        self.offset(dx, dy).expect("offset stays within the unsigned coordinate space")
+    }
+}
+
+impl Sub<(isize, isize)> for Point {
+    type Output = Point;
+    fn sub(self, (dx, dy): (isize, isize)) -> Self::Output {
+        // Not self + (-dx, -dy): negating isize::MIN overflows.
+        let (x, y) = (self.x.checked_sub_signed(dx), self.y.checked_sub_signed(dy));
+        x.zip(y).map(|(x, y)| Point { x, y }).expect("offset stays within the unsigned coordinate space")
+    }
+}
+
+impl Mul<usize> for Point {
+    type Output = Point;
+    fn mul(self, rhs: usize) -> Self::Output {
+        Point { x: self.x * rhs, y: self.y * rhs }
+    }
+}
+
+impl Point {
+    pub fn offset(&self, dx: isize, dy: isize) -> Option<Point> {
+        Some(Point { x: self.x.checked_add_signed(dx)?, y: self.y.checked_add_signed(dy)? })
+    }
+
+    pub fn line_to(&self, other: &Point) -> LineIterator {
+        LineIterator::new(self, other)
+    }
+
+    pub fn manhattan(&self, other: &Point) -> usize {
+        self.x.abs_diff(other.x).checked_add(self.y.abs_diff(other.y)).expect("manhattan distance fits in usize")
+    }
+
+    pub fn chebyshev(&self, other: &Point) -> usize {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SignedPoint {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl fmt::Display for SignedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl From<Point> for SignedPoint {
+    fn from(p: Point) -> Self {
+        SignedPoint { x: p.x as i64, y: p.y as i64 }
+    }
+}
+
+impl SignedPoint {
+    pub fn manhattan(&self, other: &SignedPoint) -> u64 {
+        self.x.abs_diff(other.x).checked_add(self.y.abs_diff(other.y)).expect("manhattan distance fits in u64")
+    }
+
+    pub fn chebyshev(&self, other: &SignedPoint) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+}
+
+// Bresenham's algorithm; both end points are included.
+#[derive(Debug)]
+pub struct LineIterator {
+    x: isize,
+    y: isize,
+    end_x: isize,
+    end_y: isize,
+    dx: isize,
+    dy: isize,
+    sx: isize,
+    sy: isize,
+    err: isize,
+    done: bool,
+}
+
+impl LineIterator {
+    pub fn new(from: &Point, to: &Point) -> Self {
+        let (x, y) = (from.x as isize, from.y as isize);
+        let (end_x, end_y) = (to.x as isize, to.y as isize);
+        let dx = (end_x - x).abs();
+        let dy = -(end_y - y).abs();
+        Self { x, y, end_x, end_y, dx, dy, sx: (end_x - x).signum(), sy: (end_y - y).signum(), err: dx + dy, done: false }
+    }
+}
+
+impl Iterator for LineIterator {
+    type Item = Point;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = Point { x: self.x as usize, y: self.y as usize };
+        if self.x == self.end_x && self.y == self.end_y {
+            self.done = true;
+        }
+        else {
+            let e2 = 2 * self.err;
+            if e2 >= self.dy {
+                self.err += self.dy;
+                self.x += self.sx;
+            }
+            if e2 <= self.dx {
+                self.err += self.dx;
+                self.y += self.sy;
+            }
+        }
+        Some(result)
+    }
+}
+
+impl FusedIterator for LineIterator {}
+
+// Inclusive of both corners
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Rect {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    // An inverted rect is empty rather than underflowing.
+    pub fn width(&self) -> usize { (self.max.x + 1).saturating_sub(self.min.x) }
+    pub fn height(&self) -> usize { (self.max.y + 1).saturating_sub(self.min.y) }
+    pub fn area(&self) -> usize { self.width() * self.height() }
+
+    pub fn bounding<I: IntoIterator<Item = Point>>(points: I) -> Option<Self> {
+        points.into_iter().fold(None, |acc: Option<Rect>, p| match acc {
+            None => Some(Rect::new(p, p)),
+            Some(r) => Some(r.union(&Rect::new(p, p))),
+        })
+    }
+
+    pub fn contains(&self, p: &Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn intersect(&self, other: &Rect) -> Option<Rect> {
+        let min = Point { x: self.min.x.max(other.min.x), y: self.min.y.max(other.min.y) };
+        let max = Point { x: self.max.x.min(other.max.x), y: self.max.y.min(other.max.y) };
+        if min.x <= max.x && min.y <= max.y { Some(Rect::new(min, max)) } else { None }
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::new(
+            Point { x: self.min.x.min(other.min.x), y: self.min.y.min(other.min.y) },
+            Point { x: self.max.x.max(other.max.x), y: self.max.y.max(other.max.y) },
+        )
+    }
+
+    // The minimum corner stops at zero rather than underflowing.
+    pub fn expand(&self, n: usize) -> Rect {
+        Rect::new(
+            Point { x: self.min.x.saturating_sub(n), y: self.min.y.saturating_sub(n) },
+            Point { x: self.max.x + n, y: self.max.y + n },
+        )
+    }
+}
+
+pub trait Grid2D {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn valid_coordinate(&self, p: &Point) -> bool;
+
+    fn iter_rect(&self, rect: &Rect) -> GridIterator where Self: Sized {
+        GridIterator::within(self, rect)
+    }
+
+    fn ray(&self, start: &Point, delta: (isize, isize)) -> Ray where Self: Sized {
+        Ray::new(self, start, delta)
+    }
+}
+
+// Walks from (but not including) `start` in steps of `delta` until it leaves the grid.
+#[derive(Debug)]
+pub struct Ray {
+    width: usize,
+    height: usize,
+    current: Option<Point>,
+    delta: (isize, isize),
+}
+
+impl Ray {
+    pub fn new<T: Grid2D>(grid: &T, start: &Point, delta: (isize, isize)) -> Self {
+        let current = if delta == (0, 0) { None } else { Some(*start) };
+        Self { width: grid.width(), height: grid.height(), current, delta }
+    }
+
+    // Stops after yielding the first point for which `stop` is true, e.g. the tree blocking a line of sight.
+    pub fn until<F: FnMut(&Point) -> bool>(self, stop: F) -> RayUntil<F> {
+        RayUntil { ray: self, stop, done: false }
+    }
+}
+
+impl Iterator for Ray {
+    type Item = Point;
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.current?;
+        self.current = p.offset(self.delta.0, self.delta.1).filter(|n| n.x < self.width && n.y < self.height);
+        self.current
+    }
+}
+
+impl FusedIterator for Ray {}
+
+pub struct RayUntil<F> {
+    ray: Ray,
+    stop: F,
+    done: bool,
+}
+
+impl<F: FnMut(&Point) -> bool> Iterator for RayUntil<F> {
+    type Item = Point;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let p = self.ray.next()?;
+        self.done = (self.stop)(&p);
+        Some(p)
+    }
+}
+
+impl<F: FnMut(&Point) -> bool> FusedIterator for RayUntil<F> {}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AoCGrid<'a> {
+    lines: Vec<&'a str>,
+    width: usize,
+    height: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    filler: Option<&'static str>,
+}
+
+// Positions are stored from 0 like every Point, but shown numbered from 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GridError {
+    Empty,
+    NonAscii { c: char, at: Point },
+    RaggedLine { y: usize, expected: usize, found: usize },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "Input should have at least one line"),
+            GridError::NonAscii { c, at } => write!(f, "Non-ASCII character {:?} on line {}, column {}; use Grid::from_chars for Unicode input", c, at.y + 1, at.x + 1),
+            GridError::RaggedLine { y, expected, found } => write!(f, "Not all lines are the same length: line {} has {} cells, expected {}", y + 1, found, expected),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RaggedLines {
+    #[default]
+    Strict,
+    TrimTrailingWhitespace,
+    // Short lines read as if padded with this cell out to the longest line
+    Pad(&'static str),
+}
+
+impl<'a> Grid2D for AoCGrid<'a> {
+    fn width(&self) -> usize { self.width }
+    fn height(&self) -> usize { self.height }
+    fn valid_coordinate(&self, p: &Point) -> bool {
+        p.x < self.width && p.y < self.height
+    }
+
+}
+
+#[derive(Debug)]
+pub struct AoCGridAdjacentPoints {
+    valid_coords: vec::IntoIter<Point>,
+}
+
+impl AoCGridAdjacentPoints {
+    pub fn new<T: Grid2D>(grid: &T, p: &Point) -> Self {
+        if !grid.valid_coordinate(p) {
+            panic!("Cannot provide adjacency for invalid coordinate {}", p);
+        }
+        const POSSIBLE_ADJACENCY: [(isize, isize); 8] = [
+            (-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)
+        ];
+        let mut valid_coords: Vec<Point> = Vec::with_capacity(POSSIBLE_ADJACENCY.len());
+        for (dx, dy) in POSSIBLE_ADJACENCY.iter() {
+            if let Some(candidate_point) = p.offset(*dx, *dy) {
+                if grid.valid_coordinate(&candidate_point) {
+                    valid_coords.push(candidate_point);
+                }
+            }
+        }
+        Self { valid_coords: valid_coords.into_iter() }
+    }
+}
+
+impl Iterator for AoCGridAdjacentPoints {
+    type Item = Point;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.valid_coords.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.valid_coords.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for AoCGridAdjacentPoints {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.valid_coords.next_back()
+    }
+}
+
+impl ExactSizeIterator for AoCGridAdjacentPoints {}
+impl FusedIterator for AoCGridAdjacentPoints {}
+
+#[derive(Debug)]
+pub struct AoCGridAdjacenyIterator<'g> {
+    grid: &'g AoCGrid<'g>,
+    inner: AoCGridAdjacentPoints,
+}
+
+impl<'g> AoCGridAdjacenyIterator<'g> {
+    pub fn new(grid: &'g AoCGrid, p: &Point) -> Self {
+        let inner = AoCGridAdjacentPoints::new(grid, p);
+        AoCGridAdjacenyIterator { grid, inner }
+    }
+}
+
+// Every point yielded by the inner iterator is valid, so each one maps to exactly one cell.
+impl<'g> Iterator for AoCGridAdjacenyIterator<'g> {
+    type Item = &'g str;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().and_then(|p| self.grid.get(&p))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'g> DoubleEndedIterator for AoCGridAdjacenyIterator<'g> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().and_then(|p| self.grid.get(&p))
+    }
+}
+
+impl<'g> ExactSizeIterator for AoCGridAdjacenyIterator<'g> {}
+impl<'g> FusedIterator for AoCGridAdjacenyIterator<'g> {}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Traversal {
+    #[default]
+    RowMajor,
+    ColumnMajor,
+    RowMajorReversed,
+    ColumnMajorReversed,
+}
+
+pub struct GridIterator {
+    origin: Point,
+    width: usize,
+    height: usize,
+    traversal: Traversal,
+    front: usize,
+    back: usize,
+}
+
+impl GridIterator {
+    pub fn new<T: Grid2D>(grid: &T) -> Self {
+        Self::with_traversal(grid, Traversal::RowMajor)
+    }
+
+    pub fn with_traversal<T: Grid2D>(grid: &T, traversal: Traversal) -> Self {
+        Self { origin: Point { x: 0, y: 0 }, width: grid.width(), height: grid.height(), traversal, front: 0, back: grid.width() * grid.height() }
+    }
+
+    // Only the part of the rectangle that lies on the grid is visited.
+    pub fn within<T: Grid2D>(grid: &T, rect: &Rect) -> Self {
+        let (width, height) = if rect.min.x <= rect.max.x && rect.min.y <= rect.max.y && grid.valid_coordinate(&rect.min) {
+            (rect.max.x.min(grid.width() - 1) + 1 - rect.min.x, rect.max.y.min(grid.height() - 1) + 1 - rect.min.y)
+        }
+        else {
+            (0, 0)
+        };
+        Self { origin: rect.min, width, height, traversal: Traversal::RowMajor, front: 0, back: width * height }
+    }
+
+    fn point_at(&self, index: usize) -> Point {
+        let last = self.width * self.height - 1;
+        let p = match self.traversal {
+            Traversal::RowMajor => Point { x: index % self.width, y: index / self.width },
+            Traversal::ColumnMajor => Point { x: index / self.height, y: index % self.height },
+            Traversal::RowMajorReversed => Point { x: (last - index) % self.width, y: (last - index) / self.width },
+            Traversal::ColumnMajorReversed => Point { x: (last - index) / self.height, y: (last - index) % self.height },
+        };
+        Point { x: self.origin.x + p.x, y: self.origin.y + p.y }
+    }
+}
+
+impl Iterator for GridIterator {
+    type Item = Point;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.front += 1;
+            Some(self.point_at(self.front - 1))
+        }
+        else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for GridIterator {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(self.point_at(self.back))
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl ExactSizeIterator for GridIterator {}
+impl FusedIterator for GridIterator {}
+
+impl<'a> AoCGrid<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_ragged_lines(input, RaggedLines::Strict)
+    }
+
+    pub fn with_ragged_lines(input: &'a str, ragged_lines: RaggedLines) -> Self {
+        Self::try_with_ragged_lines(input, ragged_lines).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(input: &'a str) -> Result<Self, GridError> {
+        Self::try_with_ragged_lines(input, RaggedLines::Strict)
+    }
+
+    pub fn try_with_ragged_lines(input: &'a str, ragged_lines: RaggedLines) -> Result<Self, GridError> {
+        let lines: Vec<&str> = match ragged_lines {
+            RaggedLines::TrimTrailingWhitespace => input.lines().map(|l| l.trim_end()).collect(),
+            _ => input.lines().collect(),
+        };
+        // Cells are byte slices, so multi-byte characters would straddle cells.
+        for (y, line) in lines.iter().enumerate() {
+            if let Some((x, c)) = line.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+                return Err(GridError::NonAscii { c, at: Point { x, y } });
+            }
+        }
+        let height: usize = lines.len();
+        if let RaggedLines::Pad(filler) = ragged_lines {
+            let width = lines.iter().map(|l| l.len()).max().ok_or(GridError::Empty)?;
+            return Ok(Self { lines, width, height, filler: Some(filler) });
+        }
+        let width: usize = lines.first().ok_or(GridError::Empty)?.len();
+        if let Some((y, line)) = lines.iter().enumerate().find(|(_, l)| l.len() != width) {
+            return Err(GridError::RaggedLine { y, expected: width, found: line.len() });
+        }
+        Ok(Self { lines, width, height, filler: None })
+    }
+
+    pub fn get(&self, p: &Point) -> Option<&str> {
+        if self.valid_coordinate(p) {
+            self.lines.get(p.y).and_then(|l| l.get(p.x..p.x+1)).or(self.filler)
+        }
+        else {
+            None
+        }
+    }
+
+    // Padding isn't stored, so a span reaching into it can't be borrowed and gives None.
+    pub fn get_str(&self, p: &Point, length: usize) -> Option<&str> {
+        if self.valid_coordinate(p) {
+            let end = (p.x + length).min(self.width);
+            self.lines.get(p.y).and_then(|l| l.get(p.x..end))
+        } else {
+            None
+        }
+    }
+
+    pub fn adjacent(&'a self, p: &Point) -> AoCGridAdjacenyIterator<'a> {
+        AoCGridAdjacenyIterator::new(self, p)
+    }
+
+    pub fn runs<F: Fn(&str) -> bool>(&'a self, predicate: F) -> RunIterator<'a, F> {
+        RunIterator { grid: self, predicate, next: Point { x: 0, y: 0 } }
+    }
+
+    pub fn to_grid<T, F: Fn(&str) -> T>(&self, f: F) -> Grid<T> {
+        Grid::from_vec(self.width, self.height, GridIterator::new(self).map(|p| f(self.get(&p).expect("valid data for valid coordinate"))).collect())
+    }
+}
+
+// Maximal horizontal runs of cells matching a predicate, as (start, length, text).
+// Runs never continue onto the next line. Padding isn't stored, so the text of a
+// run reaching into it stops at the end of the real line; length still counts it.
+pub struct RunIterator<'g, F> {
+    grid: &'g AoCGrid<'g>,
+    predicate: F,
+    next: Point,
+}
+
+impl<'g, F: Fn(&str) -> bool> RunIterator<'g, F> {
+    fn matches(&self, p: &Point) -> bool {
+        self.grid.get(p).is_some_and(|cell| (self.predicate)(cell))
+    }
+}
+
+impl<'g, F: Fn(&str) -> bool> Iterator for RunIterator<'g, F> {
+    type Item = (Point, usize, &'g str);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next.y < self.grid.height {
+            while self.next.x < self.grid.width {
+                if self.matches(&self.next) {
+                    let start = self.next;
+                    while self.next.x < self.grid.width && self.matches(&self.next) {
+                        self.next.x += 1;
+                    }
+                    let line = self.grid.lines[start.y];
+                    let text = &line[start.x.min(line.len())..self.next.x.min(line.len())];
+                    return Some((start, self.next.x - start.x, text));
+                }
+                self.next.x += 1;
+            }
+            self.next = Point { x: 0, y: self.next.y + 1 };
+        }
+        None
+    }
+}
+
+impl<'g, F: Fn(&str) -> bool> FusedIterator for RunIterator<'g, F> {}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "GridRepr<T>"))]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+// Deserialized grids go through here so the cell count is checked against the dimensions.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct GridRepr<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<GridRepr<T>> for Grid<T> {
+    type Error = String;
+    fn try_from(repr: GridRepr<T>) -> Result<Self, Self::Error> {
+        if repr.cells.len() != repr.width * repr.height {
+            return Err(format!("Expected {} cells for a {}x{} grid but got {}", repr.width * repr.height, repr.width, repr.height, repr.cells.len()));
+        }
+        Ok(Grid { cells: repr.cells, width: repr.width, height: repr.height })
+    }
+}
+
+impl<T> Grid2D for Grid<T> {
+    fn width(&self) -> usize { self.width }
+    fn height(&self) -> usize { self.height }
+    fn valid_coordinate(&self, p: &Point) -> bool {
+        p.x < self.width && p.y < self.height
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self { cells: vec![fill; width * height], width, height }
+    }
+
+    // The grid covers just the bounding box of the points, so cell (0, 0) is the returned
+    // origin. Cells without a point get `default`; a repeated point keeps its last value.
+    pub fn from_points<I: IntoIterator<Item = (Point, T)>>(points: I, default: T) -> (Self, Point) {
+        let points: Vec<(Point, T)> = points.into_iter().collect();
+        match Rect::bounding(points.iter().map(|(p, _)| *p)) {
+            Some(bounds) => {
+                let mut grid = Grid::new(bounds.width(), bounds.height(), default);
+                for (p, value) in points {
+                    grid[p - bounds.min] = value;
+                }
+                (grid, bounds.min)
+            },
+            None => (Grid::new(0, 0, default), Point { x: 0, y: 0 }),
+        }
+    }
+}
+
+impl Grid<char> {
+    // Indexed by char rather than byte, so any Unicode text works.
+    pub fn from_chars(input: &str) -> Self {
+        let rows: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+        let width = rows.first().map_or(0, |r| r.len());
+        if rows.iter().any(|r| r.len() != width) {
+            panic!("Not all lines are the same length");
+        }
+        Grid::from_vec(width, rows.len(), rows.into_iter().flatten().collect())
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Self {
+        if cells.len() != width * height {
+            panic!("Expected {} cells for a {}x{} grid but got {}", width * height, width, height, cells.len());
+        }
+        Self { cells, width, height }
+    }
+
+    pub fn get(&self, p: &Point) -> Option<&T> {
+        if self.valid_coordinate(p) { self.cells.get(p.y * self.width + p.x) } else { None }
+    }
+
+    pub fn get_mut(&mut self, p: &Point) -> Option<&mut T> {
+        if self.valid_coordinate(p) { self.cells.get_mut(p.y * self.width + p.x) } else { None }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid { cells: self.cells.iter().map(f).collect(), width: self.width, height: self.height }
+    }
+
+    pub fn runs<F: Fn(&T) -> bool>(&self, predicate: F) -> CellRunIterator<'_, T, F> {
+        CellRunIterator { grid: self, predicate, next: Point { x: 0, y: 0 }, end_y: self.height }
+    }
+
+    pub fn row_runs<F: Fn(&T) -> bool>(&self, y: usize, predicate: F) -> CellRunIterator<'_, T, F> {
+        CellRunIterator { grid: self, predicate, next: Point { x: 0, y }, end_y: (y + 1).min(self.height) }
+    }
+}
+
+// Like RunIterator, but over classified cells: maximal horizontal runs as (start, cells).
+pub struct CellRunIterator<'g, T, F> {
+    grid: &'g Grid<T>,
+    predicate: F,
+    next: Point,
+    end_y: usize,
+}
+
+impl<'g, T, F: Fn(&T) -> bool> CellRunIterator<'g, T, F> {
+    fn matches(&self, p: &Point) -> bool {
+        self.grid.get(p).is_some_and(&self.predicate)
+    }
+}
+
+impl<'g, T, F: Fn(&T) -> bool> Iterator for CellRunIterator<'g, T, F> {
+    type Item = (Point, &'g [T]);
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next.y < self.end_y {
+            while self.next.x < self.grid.width {
+                if self.matches(&self.next) {
+                    let start = self.next;
+                    while self.next.x < self.grid.width && self.matches(&self.next) {
+                        self.next.x += 1;
+                    }
+                    let row = start.y * self.grid.width;
+                    return Some((start, &self.grid.cells[row + start.x..row + self.next.x]));
+                }
+                self.next.x += 1;
+            }
+            self.next = Point { x: 0, y: self.next.y + 1 };
+        }
+        None
+    }
+}
+
+impl<'g, T, F: Fn(&T) -> bool> FusedIterator for CellRunIterator<'g, T, F> {}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+    fn index(&self, p: Point) -> &Self::Output {
+        self.get(&p).unwrap_or_else(|| panic!("{} is outside the {}x{} grid", p, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(&p).unwrap_or_else(|| panic!("{} is outside the {}x{} grid", p, width, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const TEST_INPUT: &str =
+r"467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn grid() {
+        let grid = AoCGrid::new(TEST_INPUT);
+        assert_eq!(10, grid.width);
+        assert_eq!(10, grid.height);
+        assert_eq!(Some("4"), grid.get(&Point{x:0,y:0}));
+        assert_eq!(Some("*"), grid.get(&Point{x:3,y:1}));
+        assert_eq!(None, grid.get(&Point{x:11,y:0}));
+        assert_eq!(None, grid.get(&Point{x:0,y:11}));
+        assert_eq!(vec!["4", "6", "7", ".", ".", ".", ".", "3"], grid.adjacent(&Point{x:1,y:1}).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_grid_iterator() {
+        let grid = AoCGrid::new(TEST_INPUT);
+        let points: Vec<Point> = GridIterator::new(&grid).collect();
+        assert_eq!(100, points.len());
+        for p in points {
+            assert!(grid.valid_coordinate(&p));
+        }
+    }
+
+    #[test]
+    fn grid_iterator_traits() {
+        let grid = AoCGrid::new("ab\ncd\nef");
+        let mut iter = GridIterator::new(&grid);
+        assert_eq!(6, iter.len());
+        assert_eq!(Some(Point{x:0,y:0}), iter.next());
+        assert_eq!(Some(Point{x:1,y:2}), iter.next_back());
+        assert_eq!(4, iter.len());
+        assert_eq!(vec![Point{x:0,y:2}, Point{x:1,y:1}, Point{x:0,y:1}, Point{x:1,y:0}], iter.by_ref().rev().collect::<Vec<_>>());
+        assert_eq!(None, iter.next());
+        assert_eq!(None, iter.next_back());
+    }
+
+    #[test]
+    fn traversal_orders() {
+        let grid = AoCGrid::new("ab\ncd\nef");
+        let cells = |t| GridIterator::with_traversal(&grid, t).map(|p| grid.get(&p).unwrap()).collect::<String>();
+        assert_eq!("abcdef", cells(Traversal::RowMajor));
+        assert_eq!("acebdf", cells(Traversal::ColumnMajor));
+        assert_eq!("fedcba", cells(Traversal::RowMajorReversed));
+        assert_eq!("fdbeca", cells(Traversal::ColumnMajorReversed));
+        assert_eq!("acebdf", GridIterator::with_traversal(&grid, Traversal::ColumnMajorReversed).rev().map(|p| grid.get(&p).unwrap()).collect::<String>());
+    }
+
+    #[test]
+    fn rect() {
+        let p = |x, y| Point{x, y};
+        let r = Rect::new(p(2,1), p(5,3));
+        assert_eq!((4, 3, 12), (r.width(), r.height(), r.area()));
+        assert!(r.contains(&p(2,1)) && r.contains(&p(5,3)) && r.contains(&p(3,2)));
+        assert!(!r.contains(&p(1,2)) && !r.contains(&p(6,2)) && !r.contains(&p(3,4)));
+        assert_eq!(Some(Rect::new(p(4,2), p(5,3))), r.intersect(&Rect::new(p(4,2), p(9,9))));
+        assert_eq!(Some(Rect::new(p(5,3), p(5,3))), r.intersect(&Rect::new(p(5,3), p(6,6))));
+        assert_eq!(None, r.intersect(&Rect::new(p(6,0), p(9,9))));
+        assert_eq!(Rect::new(p(0,1), p(5,8)), r.union(&Rect::new(p(0,4), p(1,8))));
+        assert_eq!(Rect::new(p(0,0), p(7,5)), r.expand(2));
+        assert_eq!(Some(Rect::new(p(1,0), p(7,4))), Rect::bounding(vec![p(3,4), p(7,0), p(1,2)]));
+        assert_eq!(None, Rect::bounding(Vec::new()));
+        let inverted = Rect::new(p(5,3), p(2,1));
+        assert_eq!((0, 0, 0), (inverted.width(), inverted.height(), inverted.area()));
+        assert_eq!(0, Rect::new(p(2,3), p(5,1)).area());
+    }
+
+    #[test]
+    fn iter_rect() {
+        let grid = AoCGrid::new(TEST_INPUT);
+        let cells = |r: Rect| grid.iter_rect(&r).map(|p| grid.get(&p).unwrap()).collect::<String>();
+        assert_eq!("114....63", cells(Rect::new(Point{x:5,y:0}, Point{x:7,y:2})));
+        assert_eq!("..", cells(Rect::new(Point{x:8,y:9}, Point{x:20,y:20})));
+        assert_eq!("", cells(Rect::new(Point{x:10,y:0}, Point{x:12,y:2})));
+        assert_eq!(4, grid.iter_rect(&Rect::new(Point{x:2,y:2}, Point{x:3,y:3})).len());
+    }
+
+    #[test]
+    fn point_arithmetic() {
+        let p = Point{x:3, y:5};
+        assert_eq!(Point{x:4, y:7}, p + Point{x:1, y:2});
+        assert_eq!(Point{x:2, y:3}, p - Point{x:1, y:2});
+        assert_eq!(Point{x:1, y:6}, p + (-2, 1));
+        assert_eq!(Point{x:5, y:4}, p - (-2, 1));
+        assert_eq!(Point{x:9, y:15}, p * 3);
+        assert_eq!(Some(Point{x:0, y:0}), p.offset(-3, -5));
+        assert_eq!(None, p.offset(-4, 0));
+        assert_eq!(None, p.offset(0, isize::MIN));
+        assert_eq!(Point{x:3, y:5 + isize::MIN.unsigned_abs()}, p - (0, isize::MIN));
+    }
+
+    #[test]
+    fn distances() {
+        let (p, q) = (Point{x:1, y:7}, Point{x:4, y:3});
+        assert_eq!(7, p.manhattan(&q));
+        assert_eq!(7, q.manhattan(&p));
+        assert_eq!(4, p.chebyshev(&q));
+        assert_eq!(0, p.chebyshev(&p));
+        let (a, b) = (SignedPoint{x:-2, y:5}, SignedPoint{x:3, y:-1});
+        assert_eq!(11, a.manhattan(&b));
+        assert_eq!(6, a.chebyshev(&b));
+        let (min, max) = (SignedPoint{x:i64::MIN, y:0}, SignedPoint{x:i64::MAX, y:0});
+        assert_eq!(u64::MAX, min.manhattan(&max));
+        assert_eq!(u64::MAX, min.chebyshev(&max));
+        assert_eq!(SignedPoint{x:1, y:7}, p.into());
+    }
+
+    #[test]
+    fn line_to() {
+        let p = |x, y| Point{x, y};
+        assert_eq!(vec![p(2,3)], p(2,3).line_to(&p(2,3)).collect::<Vec<_>>());
+        assert_eq!(vec![p(4,1), p(3,1), p(2,1)], p(4,1).line_to(&p(2,1)).collect::<Vec<_>>());
+        assert_eq!(vec![p(0,0), p(0,1), p(0,2)], p(0,0).line_to(&p(0,2)).collect::<Vec<_>>());
+        assert_eq!(vec![p(3,0), p(2,1), p(1,2), p(0,3)], p(3,0).line_to(&p(0,3)).collect::<Vec<_>>());
+        assert_eq!(vec![p(0,0), p(1,1), p(2,1), p(3,2), p(4,2)], p(0,0).line_to(&p(4,2)).collect::<Vec<_>>());
+        assert_eq!(vec![p(4,2), p(3,1), p(2,1), p(1,0), p(0,0)], p(4,2).line_to(&p(0,0)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ray() {
+        let grid = AoCGrid::new(TEST_INPUT);
+        let cells = |r: &mut dyn Iterator<Item = Point>| r.map(|p| grid.get(&p).unwrap()).collect::<String>();
+        assert_eq!("..114..", cells(&mut grid.ray(&Point{x:2,y:0}, (1, 0))));
+        assert_eq!("7.4", cells(&mut grid.ray(&Point{x:3,y:1}, (-1, -1)).chain(grid.ray(&Point{x:0,y:2}, (0, -1)))));
+        assert_eq!("", cells(&mut grid.ray(&Point{x:9,y:9}, (1, 1))));
+        assert_eq!("", cells(&mut grid.ray(&Point{x:4,y:4}, (0, 0))));
+        assert_eq!(".5", cells(&mut grid.ray(&Point{x:3,y:4}, (0, -1)).until(|p| grid.get(p) != Some("."))));
+        assert_eq!(5, grid.ray(&Point{x:3,y:4}, (0, 1)).until(|_| false).count());
+    }
+
+    #[test]
+    fn adjacency_iterator_traits() {
+        let grid = AoCGrid::new("123\n456\n789");
+        let points = AoCGridAdjacentPoints::new(&grid, &Point{x:0,y:0});
+        assert_eq!(3, points.len());
+        assert_eq!(vec![Point{x:1,y:1}, Point{x:0,y:1}, Point{x:1,y:0}], points.rev().collect::<Vec<_>>());
+        let mut adj = grid.adjacent(&Point{x:1,y:1});
+        assert_eq!(8, adj.len());
+        assert_eq!(Some("9"), adj.next_back());
+        assert_eq!(Some("1"), adj.next());
+        assert_eq!(6, adj.len());
+    }
+
+    #[test]
+    fn dense_grid() {
+        let grid = AoCGrid::new("12\n34\n56").to_grid(|s| s.parse::<u64>().unwrap());
+        assert_eq!((2, 3), (grid.width(), grid.height()));
+        assert_eq!(Some(&4), grid.get(&Point{x:1,y:1}));
+        assert_eq!(None, grid.get(&Point{x:2,y:0}));
+        assert_eq!(vec![&[1, 2][..], &[3, 4], &[5, 6]], grid.rows().collect::<Vec<_>>());
+        let mut doubled = grid.map(|v| v * 2);
+        assert_eq!(12, doubled[Point{x:1,y:2}]);
+        doubled[Point{x:0,y:0}] = 7;
+        assert_eq!(Some(&7), doubled.get(&Point{x:0,y:0}));
+        assert_eq!(Grid::from_vec(2, 1, vec!['.', '.']), Grid::new(2, 1, '.'));
+    }
+
+    #[test]
+    fn from_points() {
+        let (grid, origin) = Grid::from_points(vec![(Point{x:498,y:4}, '#'), (Point{x:500,y:6}, '#'), (Point{x:499,y:5}, 'o')], '.');
+        assert_eq!(Point{x:498,y:4}, origin);
+        assert_eq!((3, 3), (grid.width(), grid.height()));
+        assert_eq!(vec!["#..", ".o.", "..#"], grid.rows().map(|r| r.iter().collect::<String>()).collect::<Vec<_>>());
+        let (empty, origin) = Grid::from_points(Vec::new(), 0u8);
+        assert_eq!((0, 0, Point{x:0,y:0}), (empty.width(), empty.height(), origin));
+        let (single, _) = Grid::from_points(vec![(Point{x:3,y:3}, 1), (Point{x:3,y:3}, 2)], 0);
+        assert_eq!(Grid::from_vec(1, 1, vec![2]), single);
+    }
+
+    #[test]
+    fn runs() {
+        let grid = AoCGrid::new("ab..c\nd.eee\n..f..");
+        let runs: Vec<(Point, usize, &str)> = grid.runs(|s| s != ".").collect();
+        assert_eq!(vec![
+            (Point{x:0,y:0}, 2, "ab"), (Point{x:4,y:0}, 1, "c"),
+            (Point{x:0,y:1}, 1, "d"), (Point{x:2,y:1}, 3, "eee"),
+            (Point{x:2,y:2}, 1, "f"),
+        ], runs);
+        assert_eq!(0, grid.runs(|s| s == "z").count());
+        assert_eq!(vec![(Point{x:0,y:0}, 5, "ab..c")], grid.runs(|_| true).take(1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cell_runs() {
+        let grid = Grid::from_chars("ab..c\nd.eee\n..f..");
+        let runs: Vec<(Point, String)> = grid.runs(|c| *c != '.').map(|(p, cells)| (p, cells.iter().collect())).collect();
+        assert_eq!(vec![
+            (Point{x:0,y:0}, "ab".to_string()), (Point{x:4,y:0}, "c".to_string()),
+            (Point{x:0,y:1}, "d".to_string()), (Point{x:2,y:1}, "eee".to_string()),
+            (Point{x:2,y:2}, "f".to_string()),
+        ], runs);
+        assert_eq!(vec![(Point{x:1,y:1}, &['.'][..])], grid.row_runs(1, |c| *c == '.').collect::<Vec<_>>());
+        assert_eq!(0, grid.row_runs(3, |_| true).count());
+        assert_eq!(0, grid.runs(|c| *c == 'z').count());
+    }
+
+    #[test]
+    fn ragged_lines() {
+        let input = "12.\n4 \n789  ";
+        let padded = AoCGrid::with_ragged_lines(input, RaggedLines::Pad("."));
+        assert_eq!((5, 3), (padded.width(), padded.height()));
+        assert_eq!(Some(" "), padded.get(&Point{x:1,y:1}));
+        assert_eq!(Some("."), padded.get(&Point{x:4,y:0}));
+        assert_eq!(None, padded.get(&Point{x:5,y:0}));
+        assert_eq!(Some("12."), padded.get_str(&Point{x:0,y:0}, 3));
+        assert_eq!(None, padded.get_str(&Point{x:0,y:0}, 4));
+        let runs: Vec<(Point, usize, &str)> = padded.runs(|s| s != " ").collect();
+        assert_eq!(vec![
+            (Point{x:0,y:0}, 5, "12."), (Point{x:0,y:1}, 1, "4"), (Point{x:2,y:1}, 3, ""), (Point{x:0,y:2}, 3, "789"),
+        ], runs);
+        let trimmed = AoCGrid::with_ragged_lines("123 \n456\n789\t", RaggedLines::TrimTrailingWhitespace);
+        assert_eq!(3, trimmed.width());
+        assert_eq!(Some("789"), trimmed.get_str(&Point{x:0,y:2}, 10));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not all lines are the same length")]
+    fn strict_rejects_ragged_lines() {
+        AoCGrid::new("123 \n456");
+    }
+
+    #[test]
+    fn try_new() {
+        assert_eq!(Some(3), AoCGrid::try_new("123\n456").ok().map(|g| g.width()));
+        assert_eq!(Err(GridError::Empty), AoCGrid::try_new("").map(|g| g.width()));
+        assert_eq!(Err(GridError::RaggedLine { y: 2, expected: 3, found: 2 }), AoCGrid::try_new("123\n456\n78").map(|g| g.width()));
+        assert_eq!(Err(GridError::NonAscii { c: 'é', at: Point{x:1,y:1} }), AoCGrid::try_new("...\n.é.").map(|g| g.width()));
+        assert_eq!(Err(GridError::Empty), AoCGrid::try_with_ragged_lines("", RaggedLines::Pad(".")).map(|g| g.width()));
+    }
+
+    #[test]
+    fn unicode() {
+        let grid = Grid::from_chars("┌─┐\n│é│\n└─┘");
+        assert_eq!((3, 3), (grid.width(), grid.height()));
+        assert_eq!(Some(&'é'), grid.get(&Point{x:1,y:1}));
+        assert_eq!('┘', grid[Point{x:2,y:2}]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Non-ASCII character 'é' on line 2, column 2")]
+    fn ascii_grid_rejects_unicode() {
+        AoCGrid::new("...\n.é.");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let grid = Grid::from_chars("#.\n.#\n..");
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(r##"{"cells":["#",".",".","#",".","."],"width":2,"height":3}"##, json);
+        assert_eq!(grid, serde_json::from_str(&json).unwrap());
+        assert!(serde_json::from_str::<Grid<char>>(r#"{"cells":["."],"width":2,"height":3}"#).is_err());
+        assert_eq!(r#"{"lines":["12","34"],"width":2,"height":2}"#, serde_json::to_string(&AoCGrid::new("12\n34")).unwrap());
+        let rect = Rect::new(Point{x:1,y:2}, Point{x:3,y:4});
+        assert_eq!(rect, serde_json::from_str(&serde_json::to_string(&rect).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn edge_adjacency() {
+        let input = r"123
+456
+789";
+        let grid = AoCGrid::new(input);
+        let adj: Vec<&str> = grid.adjacent(&Point{x:2,y:1}).collect();
+        assert_eq!(vec!["2", "3", "5", "8", "9"], adj);
+    }
+}
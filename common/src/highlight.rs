@@ -0,0 +1,78 @@
+use crate::grid::{AoCGrid, Grid2D, Point};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Grey,
+}
+
+impl Colour {
+    fn code(&self) -> &'static str {
+        match self {
+            Colour::Red => "31",
+            Colour::Green => "32",
+            Colour::Yellow => "33",
+            Colour::Blue => "34",
+            Colour::Magenta => "35",
+            Colour::Cyan => "36",
+            Colour::Grey => "90",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+// Prints the grid with ANSI colours. Neighbouring cells of the same colour share one escape
+// sequence, and colour never carries over a line break.
+pub fn highlight<F: Fn(&Point) -> Option<Colour>>(grid: &AoCGrid, colour: F) -> String {
+    let mut out = String::new();
+    for y in 0..grid.height() {
+        let mut current: Option<Colour> = None;
+        for x in 0..grid.width() {
+            let p = Point { x, y };
+            let next = colour(&p);
+            if next != current {
+                if current.is_some() {
+                    out.push_str(RESET);
+                }
+                if let Some(c) = next {
+                    out.push_str(&format!("\x1b[{}m", c.code()));
+                }
+                current = next;
+            }
+            out.push_str(grid.get(&p).unwrap_or(" "));
+        }
+        if current.is_some() {
+            out.push_str(RESET);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_share_escapes() {
+        let grid = AoCGrid::new("12.*\n....");
+        let rendered = highlight(&grid, |p| match (p.x, p.y) {
+            (0 | 1, 0) => Some(Colour::Green),
+            (3, 0) => Some(Colour::Red),
+            _ => None,
+        });
+        assert_eq!("\x1b[32m12\x1b[0m.\x1b[31m*\x1b[0m\n....\n", rendered);
+    }
+
+    #[test]
+    fn plain() {
+        let grid = AoCGrid::new("ab\ncd");
+        assert_eq!("ab\ncd\n", highlight(&grid, |_| None));
+    }
+}
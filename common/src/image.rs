@@ -0,0 +1,134 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::grid::{Grid2D, GridIterator, Point};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+}
+
+// Dots are black, digits are shades of grey and everything else gets a
+// colour derived from its bytes, so the same symbol always looks the same.
+pub fn cell_colour(cell: &str) -> Rgb {
+    match cell {
+        "." => Rgb::BLACK,
+        "0" | "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9" => {
+            let shade = 80 + 19 * (cell.as_bytes()[0] - b'0');
+            Rgb(shade, shade, shade)
+        },
+        _ => {
+            let h = cell.bytes().fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+            Rgb(128 | (h & 0x7f) as u8, 128 | ((h >> 7) & 0x7f) as u8, 128 | ((h >> 14) & 0x7f) as u8)
+        },
+    }
+}
+
+pub struct Rasterizer<'g, G, F> {
+    grid: &'g G,
+    colour: F,
+    scale: usize,
+}
+
+impl<'g, G, F> Rasterizer<'g, G, F>
+    where G: Grid2D, F: Fn(&Point) -> Rgb
+{
+    pub fn new(grid: &'g G, colour: F) -> Self {
+        Self { grid, colour, scale: 1 }
+    }
+
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn image_width(&self) -> usize { self.grid.width() * self.scale }
+    pub fn image_height(&self) -> usize { self.grid.height() * self.scale }
+
+    pub fn pixels(&self) -> Vec<u8> {
+        let row_len = 3 * self.image_width();
+        let mut data = vec![0u8; row_len * self.image_height()];
+        for p in GridIterator::new(self.grid) {
+            let Rgb(r, g, b) = (self.colour)(&p);
+            for dy in 0..self.scale {
+                let row_start = (p.y * self.scale + dy) * row_len;
+                for dx in 0..self.scale {
+                    let i = row_start + 3 * (p.x * self.scale + dx);
+                    data[i..i+3].copy_from_slice(&[r, g, b]);
+                }
+            }
+        }
+        data
+    }
+
+    pub fn write_png<W: Write>(&self, w: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(w, self.image_width() as u32, self.image_height() as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels())?;
+        Ok(())
+    }
+
+    pub fn write_svg<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" shape-rendering="crispEdges">"#,
+            self.image_width(), self.image_height())?;
+        for p in GridIterator::new(self.grid) {
+            let Rgb(r, g, b) = (self.colour)(&p);
+            writeln!(w, r##"<rect x="{}" y="{}" width="{s}" height="{s}" fill="#{:02x}{:02x}{:02x}"/>"##,
+                p.x * self.scale, p.y * self.scale, r, g, b, s = self.scale)?;
+        }
+        writeln!(w, "</svg>")
+    }
+
+    // The format is picked from the extension: `.svg` writes SVG, anything else PNG.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let w = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("svg") => self.write_svg(w),
+            _ => self.write_png(w),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::AoCGrid;
+
+    #[test]
+    fn pixels() {
+        let grid = AoCGrid::new("1.\n.*");
+        let rasterizer = Rasterizer::new(&grid, |p| cell_colour(grid.get(p).unwrap())).scale(2);
+        assert_eq!((4, 4), (rasterizer.image_width(), rasterizer.image_height()));
+        let pixels = rasterizer.pixels();
+        assert_eq!(48, pixels.len());
+        assert_eq!(&[99, 99, 99, 99, 99, 99, 0, 0, 0, 0, 0, 0], &pixels[0..12]);
+        assert_eq!(&pixels[0..12], &pixels[12..24]);
+        let star = cell_colour("*");
+        assert_eq!(&[star.0, star.1, star.2], &pixels[42..45]);
+    }
+
+    #[test]
+    fn png_signature() {
+        let grid = AoCGrid::new("1.\n.*");
+        let mut out = Vec::new();
+        Rasterizer::new(&grid, |p| cell_colour(grid.get(p).unwrap())).write_png(&mut out).unwrap();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &out[0..8]);
+    }
+
+    #[test]
+    fn svg() {
+        let grid = AoCGrid::new("1.\n.*");
+        let mut out = Vec::new();
+        Rasterizer::new(&grid, |p| cell_colour(grid.get(p).unwrap())).scale(3).write_svg(&mut out).unwrap();
+        let svg = String::from_utf8(out).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(4, svg.matches("<rect").count());
+        assert!(svg.contains(r##"<rect x="3" y="0" width="3" height="3" fill="#000000"/>"##));
+    }
+}
@@ -0,0 +1,95 @@
+use std::ops::Sub;
+
+// The integer types an Interval can be built over.
+pub trait Bound: Copy + Ord + Sub<Output = Self> {
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_bound {
+    ($($t:ty),*) => {
+        $(impl Bound for $t {
+            fn saturating_add(self, rhs: Self) -> Self { <$t>::saturating_add(self, rhs) }
+        })*
+    };
+}
+
+impl_bound!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+// Half-open: start is included, end is not. An interval with end <= start is empty.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Interval<T = u64> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Bound> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Self { start, end: end.max(start) }
+    }
+
+    // Saturates rather than overflowing when start + length passes T::MAX.
+    pub fn with_length(start: T, length: T) -> Self {
+        Self::new(start, start.saturating_add(length))
+    }
+
+    pub fn len(&self) -> T { self.end - self.start }
+    pub fn is_empty(&self) -> bool { self.start == self.end }
+
+    pub fn contains(&self, v: T) -> bool {
+        self.start <= v && v < self.end
+    }
+
+    pub fn intersect(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let overlap = Interval::new(self.start.max(other.start), self.end.min(other.end));
+        if overlap.is_empty() { None } else { Some(overlap) }
+    }
+
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.intersect(other).is_some()
+    }
+
+    // The parts below and at-or-above v; either may be empty.
+    pub fn split_at(&self, v: T) -> (Interval<T>, Interval<T>) {
+        let v = v.clamp(self.start, self.end);
+        (Interval::new(self.start, v), Interval::new(v, self.end))
+    }
+
+    pub fn iter(&self) -> std::ops::Range<T> {
+        self.start..self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basics() {
+        let i = Interval::with_length(10, 5);
+        assert_eq!(Interval { start: 10, end: 15 }, i);
+        assert_eq!(5, i.len());
+        assert!(i.contains(10) && i.contains(14));
+        assert!(!i.contains(15) && !i.contains(9));
+        assert!(Interval::new(7, 3).is_empty());
+        assert_eq!(u64::MAX, Interval::with_length(u64::MAX - 1, 10).end);
+        assert_eq!(vec![10, 11, 12, 13, 14], i.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn intersect_and_split() {
+        let i = Interval::new(10, 20);
+        assert_eq!(Some(Interval::new(15, 20)), i.intersect(&Interval::new(15, 30)));
+        assert_eq!(None, i.intersect(&Interval::new(20, 30)));
+        assert!(!i.overlaps(&Interval::new(0, 10)));
+        assert_eq!((Interval::new(10, 12), Interval::new(12, 20)), i.split_at(12));
+        assert_eq!((Interval::new(10, 10), i), i.split_at(3));
+        assert_eq!((i, Interval::new(20, 20)), i.split_at(25));
+    }
+
+    #[test]
+    fn wide() {
+        let i: Interval<u128> = Interval::with_length(u64::MAX as u128, 10);
+        assert_eq!(u64::MAX as u128 + 10, i.end);
+        assert_eq!(Interval::new(-5i64, 0), Interval::new(-5i64, 10).split_at(0).0);
+    }
+}
@@ -0,0 +1,25 @@
+pub mod bitgrid;
+#[cfg(feature = "nom")]
+pub mod combinators;
+pub mod convention;
+pub mod diagnostic;
+pub mod diff;
+pub mod direction;
+pub mod error;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod highlight;
+#[cfg(feature = "images")]
+pub mod image;
+pub mod interval;
+pub mod linear;
+pub mod math;
+pub mod parse;
+pub mod prefix_sum;
+pub mod progress;
+pub mod rational;
+pub mod search;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+pub mod token;
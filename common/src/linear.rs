@@ -0,0 +1,98 @@
+use crate::rational::Rational;
+
+// A dense matrix of exact fractions, stored row by row.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Matrix {
+    rows: Vec<Vec<Rational>>,
+}
+
+impl Matrix {
+    pub fn new(rows: Vec<Vec<Rational>>) -> Self {
+        if rows.iter().any(|r| r.len() != rows[0].len()) {
+            panic!("Not all matrix rows are the same length");
+        }
+        Self { rows }
+    }
+
+    pub fn from_integers<T: Into<i128> + Copy>(rows: &[Vec<T>]) -> Self {
+        Self::new(rows.iter().map(|r| r.iter().map(|&n| Rational::integer(n.into())).collect()).collect())
+    }
+
+    pub fn height(&self) -> usize { self.rows.len() }
+    pub fn width(&self) -> usize { self.rows.first().map_or(0, |r| r.len()) }
+    pub fn row(&self, y: usize) -> &[Rational] { &self.rows[y] }
+
+    pub fn mul_vec(&self, v: &[Rational]) -> Option<Vec<Rational>> {
+        if v.len() != self.width() {
+            return None;
+        }
+        self.rows.iter().map(|r| r.iter().zip(v).try_fold(Rational::ZERO, |total, (a, b)| total.checked_add(&a.checked_mul(b)?))).collect()
+    }
+}
+
+// The x with a x = b, by Gauss-Jordan elimination in exact arithmetic. Pivots on the largest
+// entry left in each column. None unless a is square, b fits it and there's exactly one
+// solution, or if anything overflows.
+pub fn solve(a: Matrix, b: Vec<Rational>) -> Option<Vec<Rational>> {
+    let n = a.height();
+    if a.width() != n || b.len() != n {
+        return None;
+    }
+    // Each row with its right-hand side on the end.
+    let mut rows: Vec<Vec<Rational>> = a.rows.into_iter().zip(b).map(|(mut r, v)| { r.push(v); r }).collect();
+    for column in 0..n {
+        let pivot = (column..n).filter(|&y| !rows[y][column].is_zero()).max_by_key(|&y| rows[y][column].abs())?;
+        rows.swap(column, pivot);
+        let scale = rows[column][column].recip()?;
+        for cell in &mut rows[column][column..] {
+            *cell = cell.checked_mul(&scale)?;
+        }
+        let pivot_row = rows[column].clone();
+        for (_, row) in rows.iter_mut().enumerate().filter(|(y, _)| *y != column) {
+            let factor = row[column];
+            if factor.is_zero() {
+                continue;
+            }
+            for (cell, p) in row[column..].iter_mut().zip(&pivot_row[column..]) {
+                *cell = cell.checked_sub(&factor.checked_mul(p)?)?;
+            }
+        }
+    }
+    Some(rows.into_iter().map(|r| r[n]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_systems() {
+        let a = Matrix::from_integers(&[vec![2, 1, -1], vec![-3, -1, 2], vec![-2, 1, 2]]);
+        let b = [8, -11, -3].map(Rational::integer).to_vec();
+        assert_eq!(Some([2, 3, -1].map(Rational::integer).to_vec()), solve(a, b));
+        // Needs a row swap, and has a fractional answer.
+        let a = Matrix::from_integers(&[vec![0, 2], vec![3, 0]]);
+        assert_eq!(Some(vec![Rational::new(1, 3), Rational::new(1, 2)]), solve(a, vec![Rational::ONE; 2]));
+        let singular = Matrix::from_integers(&[vec![1, 2], vec![2, 4]]);
+        assert_eq!(None, solve(singular, vec![Rational::ONE; 2]));
+        assert_eq!(None, solve(Matrix::from_integers(&[vec![1, 2]]), vec![Rational::ONE]));
+        assert_eq!(Some(vec![]), solve(Matrix::new(vec![]), vec![]));
+    }
+
+    #[test]
+    fn six_by_six() {
+        // As big as the system for a rock thrown through three hailstones.
+        let a = Matrix::from_integers(&[
+            vec![-2, 1, 0, 6, -19, 0],
+            vec![1, 0, 0, -12, 0, 19],
+            vec![0, 3, 2, 0, 8, -22],
+            vec![-1, -2, 0, 18, 12, 0],
+            vec![0, 0, -2, 0, 25, 13],
+            vec![3, -1, 1, -20, 0, 7],
+        ]);
+        let mut x = [24, 13, 10, -3, 1, 0].map(Rational::integer).to_vec();
+        x[5] = Rational::new(5, 3);
+        let b = a.mul_vec(&x).unwrap();
+        assert_eq!(Some(x), solve(a, b));
+    }
+}
@@ -0,0 +1,312 @@
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+// The unsigned integers gcd and lcm work over.
+pub trait Unsigned: Copy + Eq + Div<Output = Self> + Mul<Output = Self> + Rem<Output = Self> {
+    const ZERO: Self;
+    const ONE: Self;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {$(
+        impl Unsigned for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            fn checked_mul(self, rhs: Self) -> Option<Self> { <$t>::checked_mul(self, rhs) }
+        }
+    )*};
+}
+
+impl_unsigned!(u64, u128, usize);
+
+// gcd(0, 0) is 0, so it is the identity for folding.
+pub fn gcd<T: Unsigned>(mut a: T, mut b: T) -> T {
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// lcm(0, n) is 0. Overflows as plain multiplication would; checked_lcm says so instead.
+pub fn lcm<T: Unsigned>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO { T::ZERO } else { a / gcd(a, b) * b }
+}
+
+pub fn checked_lcm<T: Unsigned>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO { Some(T::ZERO) } else { (a / gcd(a, b)).checked_mul(b) }
+}
+
+// The lcm of nothing is 1, so cycles that all line up at the start align again after it.
+pub fn lcm_iter<T: Unsigned, I: IntoIterator<Item = T>>(values: I) -> T {
+    values.into_iter().fold(T::ONE, lcm)
+}
+
+pub fn checked_lcm_iter<T: Unsigned, I: IntoIterator<Item = T>>(values: I) -> Option<T> {
+    values.into_iter().try_fold(T::ONE, checked_lcm)
+}
+
+// The smallest non-negative x and the modulus m such that every x + k * m satisfies all of the
+// (residue, modulus) congruences. The moduli needn't be coprime. None if they contradict each
+// other, a modulus isn't positive, or the combined modulus doesn't fit in an i128.
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences.iter().try_fold((0, 1), |(residue, modulus), &(r, m)| {
+        if m <= 0 {
+            return None;
+        }
+        let g = gcd(modulus as u128, m as u128) as i128;
+        let difference = (r - residue).rem_euclid(m);
+        if difference % g != 0 {
+            return None;
+        }
+        // residue + modulus * k, where modulus / g * k = difference / g (mod m / g)
+        let step = m / g;
+        let k = mul_mod(difference / g, inverse_mod(modulus / g % step, step)?, step);
+        let combined = (modulus / g).checked_mul(m)?;
+        let x = (residue as u128 + mul_mod(modulus, k, combined) as u128) % combined as u128;
+        Some((x as i128, combined))
+    })
+}
+
+// a * b mod m for a, b in 0..m, without the product overflowing.
+fn mul_mod(a: i128, b: i128, m: i128) -> i128 {
+    let (mut a, mut b, m) = (a as u128, b as u128, m as u128);
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = (product + a) % m;
+        }
+        a = (a + a) % m;
+        b >>= 1;
+    }
+    product as i128
+}
+
+// The x in 0..m with a * x = 1 (mod m), if a and m are coprime.
+fn inverse_mod(a: i128, m: i128) -> Option<i128> {
+    let (mut old_r, mut r) = (a, m);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    (old_r == 1).then(|| old_s.rem_euclid(m))
+}
+
+// The value at x of the lowest-degree polynomial through the (x, y) points, by Lagrange's formula
+// in exact fractions. None if two points share an x, the value isn't a whole number, or anything
+// overflows an i128 on the way.
+pub fn lagrange<T: Into<i128> + Copy>(points: &[(T, T)], x: T) -> Option<i128> {
+    let x = x.into();
+    let points: Vec<(i128, i128)> = points.iter().map(|&(px, py)| (px.into(), py.into())).collect();
+    let mut total: (i128, i128) = (0, 1);
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let mut term: (i128, i128) = (yi, 1);
+        for &(xj, _) in points.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, p)| p) {
+            term = reduce(term.0.checked_mul(x.checked_sub(xj)?)?, term.1.checked_mul(xi.checked_sub(xj)?)?)?;
+        }
+        let g = signed_gcd(total.1, term.1)?;
+        let numerator = total.0.checked_mul(term.1 / g)?.checked_add(term.0.checked_mul(total.1 / g)?)?;
+        total = reduce(numerator, total.1.checked_mul(term.1 / g)?)?;
+    }
+    (total.1 == 1).then_some(total.0)
+}
+
+// Samples taken at x = 0, 1, 2..., extended to x = n with Newton's forward differences, so the
+// working stays in whole numbers. None on overflow.
+pub fn newton_extrapolate<T: Into<i128> + Copy>(samples: &[T], n: i128) -> Option<i128> {
+    let mut differences: Vec<i128> = samples.iter().map(|&y| y.into()).collect();
+    // Binomial n choose k, as n may be past the samples or before them.
+    let (mut total, mut choose) = (0i128, 1i128);
+    for k in 0..differences.len() {
+        total = total.checked_add(choose.checked_mul(differences[0])?)?;
+        choose = choose.checked_mul(n.checked_sub(k as i128)?)? / (k as i128 + 1);
+        differences = differences.windows(2).map(|w| w[1].checked_sub(w[0])).collect::<Option<_>>()?;
+    }
+    Some(total)
+}
+
+// The usual shape for a count that grows quadratically with each repeat of a map: three samples
+// pin down the quadratic, which is then read off at target_x.
+pub fn extrapolate_quadratic<T: Into<i128> + Copy>(samples: &[(T, T); 3], target_x: T) -> Option<i128> {
+    lagrange(samples, target_x)
+}
+
+// A fraction in lowest terms with a positive denominator, or None for a zero denominator.
+pub(crate) fn reduce(numerator: i128, denominator: i128) -> Option<(i128, i128)> {
+    if denominator == 0 {
+        return None;
+    }
+    let g = signed_gcd(numerator, denominator)?;
+    let (numerator, denominator) = (numerator / g, denominator / g);
+    if denominator < 0 { Some((numerator.checked_neg()?, -denominator)) } else { Some((numerator, denominator)) }
+}
+
+// Never negative, and None only for gcd(i128::MIN, 0) and the like, which don't fit.
+pub(crate) fn signed_gcd(a: i128, b: i128) -> Option<i128> {
+    match gcd(a.unsigned_abs(), b.unsigned_abs()) {
+        0 => Some(1),
+        g => g.try_into().ok(),
+    }
+}
+
+// An integer modulo M, always held reduced into 0..M.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    // Checked when a ModInt<M> is first built, so ModInt<0> and ModInt<1> fail to compile rather
+    // than dividing by zero or underflowing M - 2.
+    const MODULUS_AT_LEAST_TWO: () = assert!(M >= 2, "ModInt needs a modulus of at least 2");
+
+    pub fn new(value: u64) -> Self {
+        let () = Self::MODULUS_AT_LEAST_TWO;
+        Self(value % M)
+    }
+
+    pub fn value(&self) -> u64 { self.0 }
+
+    pub fn pow(self, mut exponent: u64) -> Self {
+        let (mut base, mut result) = (self, Self::new(1));
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    // By Fermat's little theorem, so only right when M is prime. Zero has no inverse.
+    pub fn inverse(self) -> Option<Self> {
+        let () = Self::MODULUS_AT_LEAST_TWO;
+        (self.0 != 0).then(|| self.pow(M - 2))
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self { Self::new(value) }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(value: i64) -> Self {
+        let () = Self::MODULUS_AT_LEAST_TWO;
+        Self((value as i128).rem_euclid(M as i128) as u64)
+    }
+}
+
+impl<const M: u64> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(((self.0 as u128 + rhs.0 as u128) % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(((self.0 as u128 + M as u128 - rhs.0 as u128) % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self((self.0 as u128 * rhs.0 as u128 % M as u128) as u64)
+    }
+}
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(0), Add::add)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(1), Mul::mul)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(6u64, gcd(48, 18));
+        assert_eq!(7u64, gcd(0, 7));
+        assert_eq!(0u64, gcd(0, 0));
+        assert_eq!(144u64, lcm(48, 18));
+        assert_eq!(0u128, lcm(0, 5));
+        assert_eq!(1, lcm_iter(Vec::<usize>::new()));
+        assert_eq!(2520, lcm_iter(1..=10u64));
+        assert_eq!(Some(6_929_643_515_266_684_189u64), checked_lcm_iter([20_777, 19_199, 18_673, 16_579, 12_643, 22_411]));
+        assert_eq!(None, checked_lcm(u64::MAX, u64::MAX - 1));
+        assert_eq!(Some(u64::MAX as u128 * (u64::MAX as u128 - 1)), checked_lcm(u64::MAX as u128, u64::MAX as u128 - 1));
+        assert_eq!(None, checked_lcm_iter([u128::MAX, 2]));
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(Some((0, 1)), crt(&[]));
+        assert_eq!(Some((23, 105)), crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((4, 5)), crt(&[(-1, 5)]));
+        // Not coprime, but consistent
+        assert_eq!(Some((10, 12)), crt(&[(4, 6), (2, 4)]));
+        assert_eq!(None, crt(&[(1, 6), (2, 4)]));
+        assert_eq!(None, crt(&[(1, 0)]));
+        let big = (1i128 << 61) - 1;
+        let (x, m) = crt(&[(5, big), (7, big + 2)]).unwrap();
+        assert_eq!((5, 7, big * (big + 2)), (x % big, x % (big + 2), m));
+        assert_eq!(None, crt(&[(0, i128::MAX), (1, 2)]));
+    }
+
+    #[test]
+    fn interpolation() {
+        let cubes: Vec<(i64, i64)> = (1..=4).map(|x| (x, x * x * x - 2 * x)).collect();
+        assert_eq!(Some(980), lagrange(&cubes, 10));
+        assert_eq!(Some(-4), lagrange(&cubes, -2));
+        assert_eq!(None, lagrange(&[(0i64, 0), (2, 1)], 1));
+        assert_eq!(None, lagrange(&[(1i64, 1), (1, 2)], 3));
+        assert_eq!(Some(7), lagrange(&[(5u64, 7)], 100));
+        assert_eq!(Some(0), lagrange::<i128>(&[], 3));
+        assert_eq!(Some(68), newton_extrapolate(&[10, 13, 16, 21, 30, 45], 6));
+        assert_eq!(Some(5), newton_extrapolate(&[10, 13, 16, 21, 30, 45], -1));
+        assert_eq!(Some(0), newton_extrapolate::<i64>(&[], 6));
+        assert_eq!(None, newton_extrapolate(&[0, i128::MAX, 0], 3));
+        // Sampled 65 steps in, then once per crossing of a 131-wide repeating map.
+        let samples = [(65u64, 3_917), (196, 34_920), (327, 96_829)];
+        assert_eq!(Some(632_421_652_138_917), extrapolate_quadratic(&samples, 26_501_365));
+        assert_eq!(Some(newton_extrapolate(&[3_917, 34_920, 96_829], 202_300).unwrap()), extrapolate_quadratic(&samples, 26_501_365));
+    }
+
+    #[test]
+    fn mod_ints() {
+        type P = ModInt<1_000_000_007>;
+        let a = P::new(1_000_000_006);
+        assert_eq!(5, (a + P::new(6)).value());
+        assert_eq!(1_000_000_005, (P::new(3) - P::new(5)).value());
+        assert_eq!(1, (a * a).value());
+        assert_eq!(P::from(-1i64), a);
+        assert_eq!(1_000_000_005, P::from(-2i64 * 1_000_000_007 - 2).value());
+        assert_eq!(500_000_004, P::new(2).pow(1_000_000_005).value());
+        for n in [1, 2, 12345, 1_000_000_006] {
+            assert_eq!(P::new(1), P::new(n) * P::new(n).inverse().unwrap());
+        }
+        assert_eq!(None, P::new(0).inverse());
+        assert_eq!(P::new(3_628_800), (1..=10).map(P::new).product());
+        assert_eq!(233, (0..10).map(|n| ModInt::<256>::new(n).pow(3)).sum::<ModInt<256>>().value());
+        let big = ModInt::<{ u64::MAX }>::new(u64::MAX - 1);
+        assert_eq!((u64::MAX - 2, 1), ((big + big).value(), (big * big).value()));
+    }
+}
@@ -0,0 +1,563 @@
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::diagnostic::Diagnostic;
+
+// Strict parsing rejects anything unexpected, which is what a fresh download should be checked
+// with. Lenient parsing is for pasted examples: it ignores trailing whitespace, blank lines where
+// they carry no meaning, and lines that don't parse.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ParseMode {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+static LENIENT: AtomicBool = AtomicBool::new(false);
+
+impl ParseMode {
+    // The mode parsers use when not given one. Runners set it once from the command line; tests
+    // should pass a mode explicitly instead, since they share the process.
+    pub fn global() -> Self {
+        if LENIENT.load(Ordering::Relaxed) { ParseMode::Lenient } else { ParseMode::Strict }
+    }
+
+    pub fn set_global(self) {
+        LENIENT.store(self == ParseMode::Lenient, Ordering::Relaxed);
+    }
+
+    // A single line as this mode sees it: lenient mode trims trailing whitespace, and gives None
+    // for a blank line so it can be dropped. For readers that can't hand over the whole text.
+    pub fn line(self, l: &str) -> Option<&str> {
+        match self {
+            ParseMode::Strict => Some(l),
+            ParseMode::Lenient => Some(l.trim_end()).filter(|l| !l.is_empty()),
+        }
+    }
+
+    // Lines numbered from 1, each passed through line, keeping the original numbers.
+    pub fn lines(self, text: &str) -> impl Iterator<Item = (usize, &str)> {
+        text.lines().enumerate().filter_map(move |(i, l)| self.line(l).map(|l| (i + 1, l)))
+    }
+
+    // One record per line. Strict mode stops at the first line that fails, returning its number;
+    // lenient mode skips it.
+    pub fn parse_lines<T, E, F: FnMut(&str) -> Result<T, E>>(self, text: &str, mut parse: F) -> Result<Vec<T>, (usize, E)> {
+        let mut records = Vec::new();
+        for (line, l) in self.lines(text) {
+            match parse(l) {
+                Ok(record) => records.push(record),
+                Err(e) if self == ParseMode::Strict => return Err((line, e)),
+                Err(_) => (),
+            }
+        }
+        Ok(records)
+    }
+}
+
+// The runs of digits in s, each with a leading '-' if signed and one is there. A '-' only counts
+// as a sign when it doesn't follow a letter or digit, so "x=-3" holds -3 but "1-3" holds 1 and 3.
+fn number_spans(s: &str, signed: bool) -> impl Iterator<Item = &str> {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        while i < bytes.len() && !bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i == bytes.len() {
+            return None;
+        }
+        let mut start = i;
+        if signed && start > 0 && bytes[start - 1] == b'-' && (start == 1 || !bytes[start - 2].is_ascii_alphanumeric()) {
+            start -= 1;
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        Some(&s[start..i])
+    })
+}
+
+// Every run of digits in s, in order, ignoring whatever separates them. Fails only when a run
+// doesn't fit in T. Meant for lines like "x=-3, y=4" where the text between numbers says nothing;
+// days four to six tokenise by hand instead, as they reject "9x" and point at the bad token.
+pub fn extract_uints<T: FromStr<Err = ParseIntError>>(s: &str) -> Result<Vec<T>, ParseIntError> {
+    number_spans(s, false).map(str::parse).collect()
+}
+
+// As extract_uints, but picking up minus signs as well.
+pub fn extract_ints<T: FromStr<Err = ParseIntError>>(s: &str) -> Result<Vec<T>, ParseIntError> {
+    number_spans(s, true).map(str::parse).collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConcatDigitsError {
+    Empty,
+    InvalidGroup(String),
+    Overflow,
+}
+
+impl fmt::Display for ConcatDigitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcatDigitsError::Empty => write!(f, "no digits to join"),
+            ConcatDigitsError::InvalidGroup(s) => write!(f, "{:?} is not a group of digits", s),
+            ConcatDigitsError::Overflow => write!(f, "the joined digits are too large"),
+        }
+    }
+}
+
+impl Error for ConcatDigitsError {}
+
+// The number written by running the groups together, as if the spaces between "7  15   30" were
+// kerning: 71530. Works digit by digit, without building the joined string.
+pub fn concat_digits_u128<'a, I: IntoIterator<Item = &'a str>>(groups: I) -> Result<u128, ConcatDigitsError> {
+    let mut value: Option<u128> = None;
+    for group in groups {
+        if group.is_empty() || !group.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ConcatDigitsError::InvalidGroup(group.to_string()));
+        }
+        for b in group.bytes() {
+            let v = value.unwrap_or(0);
+            value = Some(v.checked_mul(10).and_then(|v| v.checked_add((b - b'0') as u128)).ok_or(ConcatDigitsError::Overflow)?);
+        }
+    }
+    value.ok_or(ConcatDigitsError::Empty)
+}
+
+pub fn concat_digits<'a, I: IntoIterator<Item = &'a str>>(groups: I) -> Result<u64, ConcatDigitsError> {
+    u64::try_from(concat_digits_u128(groups)?).map_err(|_| ConcatDigitsError::Overflow)
+}
+
+// Lines like day six's "Time:      7  15   30", each a label followed by numbers lined up in
+// columns. Lines are numbered from 1, in errors and rows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table<T = u64> {
+    rows: Vec<(usize, String, Vec<T>)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TableError {
+    MissingLabel { line: usize },
+    InvalidNumber { line: usize, token: String },
+    Ragged { line: usize, expected: usize, found: usize },
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::MissingLabel { line } => write!(f, "line {}: expected a label ending in ':'", line),
+            TableError::InvalidNumber { line, token } => write!(f, "line {}: {:?} is not a valid number", line, token),
+            TableError::Ragged { line, expected, found } => write!(f, "line {}: expected {} columns, found {}", line, expected, found),
+        }
+    }
+}
+
+impl Error for TableError {}
+
+impl<T: FromStr> FromStr for Table<T> {
+    type Err = TableError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Table::parse_with(s, ParseMode::global())
+    }
+}
+
+fn table_row<T: FromStr>(line: usize, l: &str) -> Result<(String, Vec<T>), TableError> {
+    let (label, values) = l.split_once(':').ok_or(TableError::MissingLabel { line })?;
+    let values: Vec<T> = values.split_ascii_whitespace()
+        .map(|token| token.parse().map_err(|_| TableError::InvalidNumber { line, token: token.to_string() }))
+        .collect::<Result<_, _>>()?;
+    Ok((label.trim().to_string(), values))
+}
+
+impl<T: FromStr> Table<T> {
+    // Lenient mode leaves out blank lines and any without a label or with a number that doesn't
+    // parse. Rows of the wrong width are an error either way.
+    pub fn parse_with(s: &str, mode: ParseMode) -> Result<Self, TableError> {
+        let mut rows: Vec<(usize, String, Vec<T>)> = Vec::new();
+        for (line, l) in mode.lines(s) {
+            let (label, values) = match table_row(line, l) {
+                Ok(row) => row,
+                Err(_) if mode == ParseMode::Lenient => continue,
+                Err(e) => return Err(e),
+            };
+            if let Some((_, _, first)) = rows.first() {
+                if first.len() != values.len() {
+                    return Err(TableError::Ragged { line, expected: first.len(), found: values.len() });
+                }
+            }
+            rows.push((line, label, values));
+        }
+        Ok(Self { rows })
+    }
+}
+
+impl<T> Table<T> {
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.rows.iter().map(|(_, label, _)| label.as_str())
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, |(_, _, values)| values.len())
+    }
+
+    // Each row's line, label and numbers, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &str, &[T])> {
+        self.rows.iter().map(|(line, label, values)| (*line, label.as_str(), values.as_slice()))
+    }
+
+    // The numbers on the row with this label, left to right.
+    pub fn row(&self, label: &str) -> Option<&[T]> {
+        self.rows.iter().find(|(_, l, _)| l == label).map(|(_, _, values)| values.as_slice())
+    }
+
+    // Column x, top to bottom.
+    pub fn column(&self, x: usize) -> Option<Vec<&T>> {
+        self.rows.iter().map(|(_, _, values)| values.get(x)).collect()
+    }
+}
+
+// What aoc_parse!'s generated FromStr reports. at is the char column where the trouble starts,
+// as Diagnostic counts them.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatError {
+    Expected { literal: &'static str, found: String, at: usize },
+    InvalidField { field: &'static str, text: String, at: usize },
+    TrailingText { text: String, at: usize },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Expected { literal, found, .. } => write!(f, "expected {:?} at {:?}", literal, found),
+            FormatError::InvalidField { field, text, .. } => write!(f, "{:?} is not a valid {}", text, field),
+            FormatError::TrailingText { text, .. } => write!(f, "unexpected {:?} at the end", text),
+        }
+    }
+}
+
+impl Error for FormatError {}
+
+impl FormatError {
+    // The offending text, or the spot where a missing literal should have started.
+    pub fn columns(&self) -> Range<usize> {
+        match self {
+            FormatError::Expected { at, .. } => *at..at + 1,
+            FormatError::InvalidField { text, at, .. } | FormatError::TrailingText { text, at } => *at..at + text.chars().count().max(1),
+        }
+    }
+
+    pub fn diagnostic(&self, source: &str, line: usize) -> Diagnostic {
+        let label = match self {
+            FormatError::Expected { literal, .. } => format!("expected {:?}", literal),
+            FormatError::InvalidField { field, .. } => format!("not a valid {}", field),
+            FormatError::TrailingText { .. } => "unexpected".to_string(),
+        };
+        Diagnostic::new(source, line, self.columns(), &self.to_string(), &label)
+    }
+}
+
+// Where part, which must be a slice of line, starts in it. Only for aoc_parse!.
+#[doc(hidden)]
+pub fn column(line: &str, part: &str) -> usize {
+    let offset = (part.as_ptr() as usize).saturating_sub(line.as_ptr() as usize).min(line.len());
+    line[..offset].chars().count()
+}
+
+// Implements FromStr for a struct from a description of its lines, e.g.
+//
+//     aoc_parse!(Game = "Game " {id} ": " {drawings: "; "});
+//
+// Literals must appear as written. A {field} takes everything up to the next literal, or the end
+// of the line, and parses it with the field type's FromStr; {field: "sep"} splits that text on
+// sep and collects the parsed pieces. Field text is trimmed and empty pieces are skipped, so
+// numbers padded out into columns still read; {exact field} and {exact field: "sep"} do neither,
+// for formats where stray spaces are an error. Two fields can't be adjacent.
+//
+// Errors are FormatErrors, unless a type is named, as in aoc_parse!(Game: ParseGameError = ...),
+// when they are converted with its From<FormatError>.
+#[macro_export]
+macro_rules! aoc_parse {
+    ($ty:ident = $($parts:tt)+) => {
+        $crate::aoc_parse!($ty: $crate::parse::FormatError = $($parts)+);
+    };
+    ($ty:ident: $err:ty = $($parts:tt)+) => {
+        impl ::std::str::FromStr for $ty {
+            type Err = $err;
+            fn from_str(line: &str) -> Result<Self, Self::Err> {
+                let parse = |rest: &str| -> Result<Self, $crate::parse::FormatError> {
+                    $crate::aoc_parse!(@parts $ty line rest [] $($parts)+)
+                };
+                parse(line).map_err(<$err>::from)
+            }
+        }
+    };
+    (@parts $ty:ident $line:ident $rest:ident [$($done:ident)*] $literal:literal $($tail:tt)*) => {{
+        let $rest = $rest.strip_prefix($literal).ok_or_else(|| $crate::parse::FormatError::Expected {
+            literal: $literal, found: $rest.to_string(), at: $crate::parse::column($line, $rest),
+        })?;
+        $crate::aoc_parse!(@parts $ty $line $rest [$($done)*] $($tail)*)
+    }};
+    (@parts $ty:ident $line:ident $rest:ident [$($done:ident)*] { exact $field:ident $(: $sep:literal)? } $literal:literal $($tail:tt)*) => {{
+        let (text, $rest) = $crate::aoc_parse!(@until $line $rest $literal);
+        let $field = $crate::aoc_parse!(@field exact $field $line text $($sep)?);
+        $crate::aoc_parse!(@parts $ty $line $rest [$($done)* $field] $($tail)*)
+    }};
+    (@parts $ty:ident $line:ident $rest:ident [$($done:ident)*] { $field:ident $(: $sep:literal)? } $literal:literal $($tail:tt)*) => {{
+        let (text, $rest) = $crate::aoc_parse!(@until $line $rest $literal);
+        let $field = $crate::aoc_parse!(@field trim $field $line text $($sep)?);
+        $crate::aoc_parse!(@parts $ty $line $rest [$($done)* $field] $($tail)*)
+    }};
+    (@parts $ty:ident $line:ident $rest:ident [$($done:ident)*] { exact $field:ident $(: $sep:literal)? }) => {{
+        let $field = $crate::aoc_parse!(@field exact $field $line $rest $($sep)?);
+        Ok($ty { $($done,)* $field })
+    }};
+    (@parts $ty:ident $line:ident $rest:ident [$($done:ident)*] { $field:ident $(: $sep:literal)? }) => {{
+        let $field = $crate::aoc_parse!(@field trim $field $line $rest $($sep)?);
+        Ok($ty { $($done,)* $field })
+    }};
+    (@parts $ty:ident $line:ident $rest:ident [$($done:ident)*]) => {
+        if $rest.is_empty() {
+            Ok($ty { $($done),* })
+        }
+        else {
+            Err($crate::parse::FormatError::TrailingText { text: $rest.to_string(), at: $crate::parse::column($line, $rest) })
+        }
+    };
+    (@until $line:ident $rest:ident $literal:literal) => {
+        $rest.split_once($literal).ok_or_else(|| $crate::parse::FormatError::Expected {
+            literal: $literal, found: $rest.to_string(), at: $crate::parse::column($line, $rest),
+        })?
+    };
+    (@field trim $field:ident $line:ident $text:ident) => {
+        $crate::aoc_parse!(@piece $field $line $text.trim())?
+    };
+    (@field exact $field:ident $line:ident $text:ident) => {
+        $crate::aoc_parse!(@piece $field $line $text)?
+    };
+    (@field trim $field:ident $line:ident $text:ident $sep:literal) => {
+        $text.split($sep).map(str::trim).filter(|t| !t.is_empty())
+            .map(|t| $crate::aoc_parse!(@piece $field $line t))
+            .collect::<Result<_, _>>()?
+    };
+    (@field exact $field:ident $line:ident $text:ident $sep:literal) => {
+        $text.split($sep).map(|t| $crate::aoc_parse!(@piece $field $line t)).collect::<Result<_, _>>()?
+    };
+    (@piece $field:ident $line:ident $text:expr) => {{
+        let text: &str = $text;
+        text.parse().map_err(|_| $crate::parse::FormatError::InvalidField {
+            field: stringify!($field), text: text.to_string(), at: $crate::parse::column($line, text),
+        })
+    }};
+}
+
+#[cfg(feature = "regex")]
+pub use self::captures::{captures_parse, CaptureError, FromCaptures};
+
+// Typed extraction of a regex's numbered groups, for days whose lines are easiest described as
+// one regex.
+#[cfg(feature = "regex")]
+mod captures {
+    use std::error::Error;
+    use std::fmt;
+    use std::str::FromStr;
+
+    use regex::{Captures, Regex};
+
+    // Groups are numbered from 1, as in the regex.
+    #[derive(Debug, PartialEq, Eq)]
+    pub enum CaptureError {
+        NoMatch(String),
+        MissingGroup(usize),
+        InvalidGroup { group: usize, text: String },
+    }
+
+    impl fmt::Display for CaptureError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                CaptureError::NoMatch(s) => write!(f, "{:?} is not in the expected format", s),
+                CaptureError::MissingGroup(group) => write!(f, "group {} did not match", group),
+                CaptureError::InvalidGroup { group, text } => write!(f, "{:?} in group {} is not the expected type", text, group),
+            }
+        }
+    }
+
+    impl Error for CaptureError {}
+
+    // Built from groups 1, 2, ... of a match, in order.
+    pub trait FromCaptures: Sized {
+        fn from_captures(captures: &Captures) -> Result<Self, CaptureError>;
+    }
+
+    fn group<T: FromStr>(captures: &Captures, group: usize) -> Result<T, CaptureError> {
+        let text = captures.get(group).ok_or(CaptureError::MissingGroup(group))?.as_str();
+        text.parse().map_err(|_| CaptureError::InvalidGroup { group, text: text.to_string() })
+    }
+
+    macro_rules! impl_from_captures {
+        ($(($($t:ident $i:literal),+))*) => {
+            $(impl<$($t: FromStr),+> FromCaptures for ($($t,)+) {
+                fn from_captures(captures: &Captures) -> Result<Self, CaptureError> {
+                    Ok(($(group::<$t>(captures, $i)?,)+))
+                }
+            })*
+        };
+    }
+
+    impl_from_captures!(
+        (A 1)
+        (A 1, B 2)
+        (A 1, B 2, C 3)
+        (A 1, B 2, C 3, D 4)
+        (A 1, B 2, C 3, D 4, E 5)
+        (A 1, B 2, C 3, D 4, E 5, F 6)
+    );
+
+    // e.g. captures_parse::<(u64, String, i64)>(&re, line) for a regex with three groups.
+    pub fn captures_parse<T: FromCaptures>(re: &Regex, line: &str) -> Result<T, CaptureError> {
+        T::from_captures(&re.captures(line).ok_or_else(|| CaptureError::NoMatch(line.to_string()))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        let text = "1\n\n2  \nx\n3";
+        assert_eq!(vec![(1, "1"), (2, ""), (3, "2  "), (4, "x"), (5, "3")], ParseMode::Strict.lines(text).collect::<Vec<_>>());
+        assert_eq!(vec![(1, "1"), (3, "2"), (4, "x"), (5, "3")], ParseMode::Lenient.lines(text).collect::<Vec<_>>());
+        assert_eq!((Some("2"), None, Some("2  ")), (ParseMode::Lenient.line("2  "), ParseMode::Lenient.line(" \t"), ParseMode::Strict.line("2  ")));
+        assert_eq!(Err(2), ParseMode::Strict.parse_lines(text, str::parse::<u8>).map_err(|(line, _)| line));
+        assert_eq!(Ok(vec![1, 2, 3]), ParseMode::Lenient.parse_lines(text, str::parse::<u8>).map_err(|(line, _)| line));
+        assert_eq!(ParseMode::Strict, ParseMode::global());
+    }
+
+    #[test]
+    fn extracts() {
+        assert_eq!(Ok(vec![1u64, 41, 48, 83, 6]), extract_uints("Card   1: 41 48 | 83  6"));
+        assert_eq!(Ok(vec![79u64, 14, 55, 13]), extract_uints("seeds: 79 14 55 13"));
+        assert_eq!(Ok(Vec::<u64>::new()), extract_uints("humidity-to-location map:"));
+        assert_eq!(Ok(vec![3u32, 5, 2]), extract_uints("x=-3, y=5-2"));
+        assert_eq!(Ok(vec![-3i64, 5, 2, -7]), extract_ints("x=-3, y=5-2 -7"));
+        assert_eq!(Ok(vec![-12i32]), extract_ints("-12"));
+        assert!(extract_uints::<u8>("1 256").is_err());
+        assert_eq!(Ok(vec![u64::MAX]), extract_uints("18446744073709551615"));
+    }
+
+    #[test]
+    fn concat() {
+        assert_eq!(Ok(71530), concat_digits("7  15   30".split_ascii_whitespace()));
+        assert_eq!(Ok(940200), concat_digits(["9", "40", "200"]));
+        assert_eq!(Ok(7), concat_digits(["007"]));
+        assert_eq!(Err(ConcatDigitsError::Empty), concat_digits("   ".split_ascii_whitespace()));
+        assert_eq!(Err(ConcatDigitsError::InvalidGroup("-4".to_string())), concat_digits(["9", "-4"]));
+        assert_eq!(Err(ConcatDigitsError::Overflow), concat_digits(["9999999999", "9999999999"]));
+        assert_eq!(Ok(99999999999999999999), concat_digits_u128(["9999999999", "9999999999"]));
+        assert_eq!(Err(ConcatDigitsError::Overflow), concat_digits_u128(["9999999999"; 4]));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn captures() {
+        let re = regex::Regex::new(r"^(\w+) (\d+) at (-?\d+)(?: (x))?$").unwrap();
+        assert_eq!(Ok(("red".to_string(), 12u64, -3i64)), captures_parse(&re, "red 12 at -3"));
+        assert_eq!(Ok(("a".to_string(), 7u8)), captures_parse(&re, "a 7 at 1"));
+        assert_eq!(Err(CaptureError::NoMatch("red at -3".to_string())), captures_parse::<(String,)>(&re, "red at -3"));
+        assert_eq!(Err(CaptureError::InvalidGroup { group: 2, text: "300".to_string() }), captures_parse::<(String, u8)>(&re, "red 300 at 1"));
+        assert_eq!(Err(CaptureError::MissingGroup(4)), captures_parse::<(String, u64, i64, char)>(&re, "red 12 at -3"));
+        assert_eq!(Ok(("red".to_string(), 12u64, -3i64, 'x')), captures_parse(&re, "red 12 at -3 x"));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Game {
+        id: u64,
+        drawings: Vec<String>,
+    }
+
+    crate::aoc_parse!(Game = "Game " {id} ": " {drawings: "; "});
+
+    #[derive(Debug, PartialEq)]
+    struct Card {
+        id: u32,
+        winning: Vec<u8>,
+        chosen: Vec<u8>,
+    }
+
+    crate::aoc_parse!(Card = "Card" {id} ":" {winning: " "} "|" {chosen: " "});
+
+    #[test]
+    fn aoc_parse() {
+        let game = Game { id: 1, drawings: vec!["3 blue, 4 red".to_string(), "2 green".to_string()] };
+        assert_eq!(Ok(game), "Game 1: 3 blue, 4 red; 2 green".parse());
+        let card = Card { id: 3, winning: vec![1, 21], chosen: vec![69, 1] };
+        assert_eq!(Ok(card), "Card   3:  1 21 | 69  1".parse());
+        assert_eq!(Err(FormatError::Expected { literal: "Game ", found: "Card 1: 2".to_string(), at: 0 }), "Card 1: 2".parse::<Game>());
+        assert_eq!(Err(FormatError::Expected { literal: ": ", found: "1 3 blue".to_string(), at: 5 }), "Game 1 3 blue".parse::<Game>());
+        assert_eq!(Err(FormatError::InvalidField { field: "id", text: "x".to_string(), at: 5 }), "Game x: 3 blue".parse::<Game>());
+        let error = "Card 1: 1 | 1 300".parse::<Card>().unwrap_err();
+        assert_eq!(FormatError::InvalidField { field: "chosen", text: "300".to_string(), at: 14 }, error);
+        let diagnostic = error.diagnostic("Card 1: 1 | 1 300", 1);
+        assert_eq!((14..17, "not a valid chosen"), (diagnostic.columns, diagnostic.label.as_str()));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Exact {
+        id: u8,
+        names: Vec<String>,
+    }
+
+    crate::aoc_parse!(Exact = "#" {exact id} ": " {exact names: ", "} ".");
+
+    #[derive(Debug, PartialEq)]
+    struct Checked {
+        n: u8,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct CheckedError(usize);
+
+    impl From<FormatError> for CheckedError {
+        fn from(e: FormatError) -> Self {
+            CheckedError(e.columns().start)
+        }
+    }
+
+    crate::aoc_parse!(Checked: CheckedError = "n=" {n});
+
+    #[test]
+    fn aoc_parse_exact_and_converted() {
+        assert_eq!(Ok(Exact { id: 4, names: vec!["a".to_string(), "".to_string(), " b".to_string()] }), "#4: a, ,  b.".parse());
+        assert_eq!(Err(FormatError::InvalidField { field: "id", text: " 4".to_string(), at: 1 }), "# 4: a.".parse::<Exact>());
+        assert_eq!(Err(FormatError::TrailingText { text: "!".to_string(), at: 6 }), "#4: a.!".parse::<Exact>());
+        assert_eq!(Ok(Checked { n: 7 }), "n= 7 ".parse());
+        assert_eq!(Err(CheckedError(3)), "n= 700".parse::<Checked>());
+    }
+
+    #[test]
+    fn table() {
+        let table: Table = "Time:      7  15   30\nDistance:  9  40  200\n".parse().unwrap();
+        assert_eq!(vec!["Time", "Distance"], table.labels().collect::<Vec<_>>());
+        assert_eq!(3, table.width());
+        assert_eq!(Some(&[9, 40, 200][..]), table.row("Distance"));
+        assert_eq!(None, table.row("Speed"));
+        assert_eq!(Some(vec![&15, &40]), table.column(1));
+        assert_eq!(None, table.column(3));
+        assert_eq!(vec![(1, "Time", &[7, 15, 30][..]), (2, "Distance", &[9, 40, 200][..])], table.rows().collect::<Vec<_>>());
+        let pasted = "\n  a: -1 2  \nnotes\nc: 1 x\n\n  b: 3 -4";
+        let table: Table<i32> = Table::parse_with(pasted, ParseMode::Lenient).unwrap();
+        assert_eq!(Some(vec![&-1, &3]), table.column(0));
+        assert_eq!(vec![2, 6], table.rows().map(|(line, _, _)| line).collect::<Vec<_>>());
+        assert_eq!(Err(TableError::MissingLabel { line: 1 }), Table::<i32>::parse_with(pasted, ParseMode::Strict));
+        let ragged = "Time: 7 15 30\nDistance: 9 40";
+        assert_eq!(Err(TableError::Ragged { line: 2, expected: 3, found: 2 }), Table::<u64>::parse_with(ragged, ParseMode::Strict));
+        assert_eq!(Err(TableError::Ragged { line: 2, expected: 3, found: 2 }), Table::<u64>::parse_with(ragged, ParseMode::Lenient));
+        assert_eq!(Err(TableError::MissingLabel { line: 1 }), Table::<u64>::parse_with("Time 7 15", ParseMode::Strict));
+        assert_eq!(Err(TableError::InvalidNumber { line: 1, token: "1x".to_string() }), Table::<u64>::parse_with("Time: 1x", ParseMode::Strict));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::grid::{Grid, Grid2D, Point, Rect};
+
+// sums[y][x] holds the total of every cell above and to the left of (x, y), exclusive.
+#[derive(Clone, Debug)]
+pub struct PrefixSumGrid {
+    sums: Vec<u64>,
+    width: usize,
+    height: usize,
+}
+
+impl PrefixSumGrid {
+    pub fn new(grid: &Grid<u64>) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let stride = width + 1;
+        let mut sums = vec![0u64; stride * (height + 1)];
+        for y in 0..height {
+            let mut row_total = 0;
+            for x in 0..width {
+                row_total += grid[Point { x, y }];
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_total;
+            }
+        }
+        Self { sums, width, height }
+    }
+
+    fn at(&self, x: usize, y: usize) -> u64 {
+        self.sums[y * (self.width + 1) + x]
+    }
+
+    // Any part of the rectangle that lies off the grid contributes nothing.
+    pub fn sum(&self, rect: &Rect) -> u64 {
+        if self.width == 0 || self.height == 0 {
+            return 0;
+        }
+        let bounds = Rect::new(Point { x: 0, y: 0 }, Point { x: self.width - 1, y: self.height - 1 });
+        match rect.intersect(&bounds) {
+            Some(r) => self.at(r.max.x + 1, r.max.y + 1) + self.at(r.min.x, r.min.y) - self.at(r.min.x, r.max.y + 1) - self.at(r.max.x + 1, r.min.y),
+            None => 0,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.at(self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectangle_sums() {
+        let grid = Grid::from_vec(4, 3, (1..=12).collect());
+        let sums = PrefixSumGrid::new(&grid);
+        let r = |x0, y0, x1, y1| Rect::new(Point{x:x0, y:y0}, Point{x:x1, y:y1});
+        assert_eq!(78, sums.total());
+        assert_eq!(78, sums.sum(&r(0,0,3,2)));
+        assert_eq!(1, sums.sum(&r(0,0,0,0)));
+        assert_eq!(7 + 8 + 11 + 12, sums.sum(&r(2,1,3,2)));
+        assert_eq!(2 + 6 + 10, sums.sum(&r(1,0,1,2)));
+        assert_eq!(12, sums.sum(&r(3,2,10,10)));
+        assert_eq!(0, sums.sum(&r(4,0,9,9)));
+        for rect in [r(0,0,2,1), r(1,1,3,2), r(2,0,2,2)] {
+            assert_eq!(grid.iter_rect(&rect).map(|p| grid[p]).sum::<u64>(), sums.sum(&rect));
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// A progress line on stderr that can be advanced from many threads. It redraws at most once
+// per interval so hot loops can call advance freely.
+pub struct Progress {
+    label: String,
+    total: u64,
+    done: AtomicU64,
+    started: Instant,
+    last_draw: Mutex<Instant>,
+    interval: Duration,
+    visible: bool,
+}
+
+impl Progress {
+    pub fn new(label: &str, total: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            label: label.to_string(),
+            total,
+            done: AtomicU64::new(0),
+            started: now,
+            last_draw: Mutex::new(now),
+            interval: Duration::from_millis(250),
+            visible: true,
+        }
+    }
+
+    // Still counts, but never draws.
+    pub fn hidden(mut self) -> Self {
+        self.visible = false;
+        self
+    }
+
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    // The status closure is only called when the line is actually redrawn.
+    pub fn advance<F: FnOnce() -> String>(&self, n: u64, status: F) {
+        let done = self.done.fetch_add(n, Ordering::Relaxed) + n;
+        if !self.visible {
+            return;
+        }
+        if let Ok(mut last_draw) = self.last_draw.try_lock() {
+            if last_draw.elapsed() >= self.interval {
+                *last_draw = Instant::now();
+                eprint!("\r{}", format_line(&self.label, done, self.total, self.started.elapsed(), &status()));
+            }
+        }
+    }
+
+    pub fn finish(&self, status: &str) {
+        if self.visible {
+            eprintln!("\r{}", format_line(&self.label, self.done(), self.total, self.started.elapsed(), status));
+        }
+    }
+}
+
+pub fn eta(done: u64, total: u64, elapsed: Duration) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    let remaining = total.saturating_sub(done) as f64;
+    Some(elapsed.mul_f64(remaining / done as f64))
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+    else {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+pub fn format_line(label: &str, done: u64, total: u64, elapsed: Duration, status: &str) -> String {
+    let percent = if total == 0 { 100.0 } else { 100.0 * done as f64 / total as f64 };
+    let eta = eta(done, total, elapsed).map_or("?".to_string(), format_duration);
+    let line = format!("{}: {}/{} ({:.1}%) ETA {}", label, done, total, percent, eta);
+    if status.is_empty() { line } else { format!("{} {}", line, status) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eta_and_line() {
+        assert_eq!(None, eta(0, 100, Duration::from_secs(5)));
+        assert_eq!(Some(Duration::from_secs(15)), eta(25, 100, Duration::from_secs(5)));
+        assert_eq!(Some(Duration::ZERO), eta(100, 100, Duration::from_secs(5)));
+        assert_eq!("seeds: 25/100 (25.0%) ETA 0m15s best 7", format_line("seeds", 25, 100, Duration::from_secs(5), "best 7"));
+        assert_eq!("seeds: 1/4 (25.0%) ETA 2h00m", format_line("seeds", 1, 4, Duration::from_secs(2400), ""));
+    }
+
+    #[test]
+    fn counts_across_threads() {
+        let progress = Progress::new("work", 400).hidden();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| (0..100).for_each(|_| progress.advance(1, String::new)));
+            }
+        });
+        assert_eq!(400, progress.done());
+    }
+}
@@ -0,0 +1,215 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::math::{reduce, signed_gcd};
+
+// An exact fraction, kept in lowest terms with a positive denominator so equal values compare
+// and hash equal. The checked_ methods return None where an i128 would overflow; the operators
+// panic there instead.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { numerator: 0, denominator: 1 };
+    pub const ONE: Rational = Rational { numerator: 1, denominator: 1 };
+
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        Self::checked_new(numerator, denominator).expect("Rational with a zero denominator")
+    }
+
+    pub fn checked_new(numerator: i128, denominator: i128) -> Option<Self> {
+        reduce(numerator, denominator).map(|(numerator, denominator)| Self { numerator, denominator })
+    }
+
+    pub fn integer(n: i128) -> Self { Self { numerator: n, denominator: 1 } }
+    pub fn numerator(&self) -> i128 { self.numerator }
+    pub fn denominator(&self) -> i128 { self.denominator }
+    pub fn is_zero(&self) -> bool { self.numerator == 0 }
+    pub fn is_integer(&self) -> bool { self.denominator == 1 }
+    pub fn to_integer(&self) -> Option<i128> { self.is_integer().then_some(self.numerator) }
+    pub fn to_f64(&self) -> f64 { self.numerator as f64 / self.denominator as f64 }
+
+    // Panics on an i128::MIN numerator, like the operators; checked_abs gives None instead.
+    pub fn abs(&self) -> Self {
+        self.checked_abs().expect("Rational overflow")
+    }
+
+    pub fn checked_abs(&self) -> Option<Self> {
+        if self.numerator < 0 { self.checked_neg() } else { Some(*self) }
+    }
+
+    pub fn recip(&self) -> Option<Self> {
+        Self::checked_new(self.denominator, self.numerator)
+    }
+
+    pub fn checked_neg(&self) -> Option<Self> {
+        Some(Self { numerator: self.numerator.checked_neg()?, denominator: self.denominator })
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        let g = signed_gcd(self.denominator, rhs.denominator)?;
+        let numerator = self.numerator.checked_mul(rhs.denominator / g)?.checked_add(rhs.numerator.checked_mul(self.denominator / g)?)?;
+        Self::checked_new(numerator, self.denominator.checked_mul(rhs.denominator / g)?)
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.checked_add(&rhs.checked_neg()?)
+    }
+
+    // Cancels across before multiplying, so products that reduce never overflow on the way.
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        let (g1, g2) = (signed_gcd(self.numerator, rhs.denominator)?, signed_gcd(rhs.numerator, self.denominator)?);
+        let numerator = (self.numerator / g1).checked_mul(rhs.numerator / g2)?;
+        Self::checked_new(numerator, (self.denominator / g2).checked_mul(rhs.denominator / g1)?)
+    }
+
+    // None for division by zero as well as overflow.
+    pub fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        self.checked_mul(&rhs.recip()?)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self { Self::integer(n as i128) }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self { Self::integer(n) }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        }
+        else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+impl Ord for Rational {
+    // By whole parts, then the reciprocals of what's left over, the other way round, so nothing
+    // is ever cross-multiplied.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.numerator.div_euclid(self.denominator), other.numerator.div_euclid(other.denominator));
+        if a != b {
+            return a.cmp(&b);
+        }
+        match (self.numerator.rem_euclid(self.denominator), other.numerator.rem_euclid(other.denominator)) {
+            (0, 0) => Ordering::Equal,
+            (0, _) => Ordering::Less,
+            (_, 0) => Ordering::Greater,
+            (r, s) => Rational { numerator: other.denominator, denominator: s }.cmp(&Rational { numerator: self.denominator, denominator: r }),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("Rational overflow")
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self, rhs: Rational) -> Self::Output {
+        self.checked_add(&rhs).expect("Rational overflow")
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self, rhs: Rational) -> Self::Output {
+        self.checked_sub(&rhs).expect("Rational overflow")
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self, rhs: Rational) -> Self::Output {
+        self.checked_mul(&rhs).expect("Rational overflow")
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+    fn div(self, rhs: Rational) -> Self::Output {
+        if rhs.is_zero() {
+            panic!("Rational division by zero");
+        }
+        self.checked_div(&rhs).expect("Rational overflow")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let r = Rational::new;
+        assert_eq!(r(1, 2), r(-3, -6));
+        assert_eq!((-2, 3), (r(4, -6).numerator(), r(4, -6).denominator()));
+        assert_eq!(r(5, 6), r(1, 2) + r(1, 3));
+        assert_eq!(r(1, 6), r(1, 2) - r(1, 3));
+        assert_eq!(Rational::ONE, r(2, 3) * r(3, 2));
+        assert_eq!(r(-3, 4), r(1, 2) / r(-2, 3));
+        assert_eq!(Some(4), (r(8, 3) * Rational::from(3i64) / r(2, 1)).to_integer());
+        assert_eq!(None, r(1, 2).to_integer());
+        assert_eq!(None, Rational::ZERO.recip());
+        assert_eq!(None, Rational::ONE.checked_div(&Rational::ZERO));
+        assert_eq!(None, Rational::checked_new(1, 0));
+        assert_eq!(("-7/2", "5"), (r(7, -2).to_string().as_str(), Rational::integer(5).to_string().as_str()));
+        assert_eq!(0.25, r(1, 4).to_f64());
+        assert_eq!(r(3, 7), r(-3, 7).abs());
+        // 0.1 + 0.2 is exactly 0.3 here.
+        assert_eq!(r(3, 10), r(1, 10) + r(2, 10));
+    }
+
+    #[test]
+    fn overflow() {
+        let big = Rational::integer(i128::MAX);
+        assert_eq!(None, big.checked_add(&Rational::ONE));
+        assert_eq!(None, big.checked_mul(&Rational::integer(2)));
+        assert_eq!(None, Rational::integer(i128::MIN).checked_neg());
+        assert_eq!(None, Rational::integer(i128::MIN).checked_abs());
+        assert_eq!(Some(big), Rational::integer(-i128::MAX).checked_abs());
+        // Cancels rather than overflowing.
+        assert_eq!(Some(Rational::ONE), big.checked_mul(&Rational::new(1, i128::MAX)));
+        let huge = Rational::new(i128::MAX, i128::MAX - 1);
+        assert_eq!(Some(Rational::ZERO), huge.checked_sub(&huge));
+    }
+
+    #[test]
+    #[should_panic(expected = "Rational overflow")]
+    fn abs_of_min_panics() {
+        Rational::new(i128::MIN, 3).abs();
+    }
+
+    #[test]
+    fn ordering() {
+        let r = Rational::new;
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(-1, 2) < r(-1, 3));
+        assert!(r(7, 3) > r(9, 4));
+        assert_eq!(Ordering::Equal, r(2, 4).cmp(&r(1, 2)));
+        // Cross-multiplying these would overflow.
+        let (a, b) = (r(i128::MAX, i128::MAX - 1), r(i128::MAX - 1, i128::MAX - 2));
+        assert!(a < b);
+        let mut values = vec![r(3, 2), r(-5, 1), r(1, 3), Rational::ZERO, r(1, 2)];
+        values.sort();
+        assert_eq!(vec![r(-5, 1), Rational::ZERO, r(1, 3), r(1, 2), r(3, 2)], values);
+    }
+}
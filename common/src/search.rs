@@ -0,0 +1,306 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::bitgrid::BitGrid;
+use crate::graph::{Graph, NodeId};
+use crate::grid::Point;
+
+// What a search settled: the shortest distance to each state it reached, and the state each was
+// reached from. Only states settled before the search stopped are included.
+#[derive(Clone, Debug)]
+pub struct Paths<S, C> {
+    pub start: S,
+    pub distances: HashMap<S, C>,
+    pub predecessors: HashMap<S, S>,
+    // The first goal settled, if any; it is the nearest one.
+    pub goal: Option<S>,
+}
+
+impl<S: Clone + Eq + Hash, C> Paths<S, C> {
+    // From start to to, both included.
+    pub fn path_to(&self, to: &S) -> Option<Vec<S>> {
+        if !self.distances.contains_key(to) {
+            return None;
+        }
+        let mut path = vec![to.clone()];
+        while let Some(previous) = self.predecessors.get(path.last()?) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// Dijkstra's algorithm over states whose successors and step costs come from a closure, so the
+// graph needn't exist up front. Stops once a goal is settled; pass |_| false to settle
+// everything reachable. Costs start from C::default() and must not be negative.
+pub fn dijkstra_by<S, C, F, I, G>(start: S, mut successors: F, mut is_goal: G) -> Paths<S, C>
+    where S: Clone + Eq + Hash,
+          C: Copy + Ord + Add<Output = C> + Default,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, C)>,
+          G: FnMut(&S) -> bool
+{
+    let mut paths = Paths { start: start.clone(), distances: HashMap::new(), predecessors: HashMap::new(), goal: None };
+    // The best cost and predecessor found so far for states not yet settled.
+    let mut tentative: HashMap<S, (C, Option<S>)> = HashMap::from([(start.clone(), (C::default(), None))]);
+    // The heap holds indices into states, so S needn't be Ord.
+    let mut states = vec![start];
+    let mut heap = BinaryHeap::from([Reverse((C::default(), 0))]);
+    while let Some(Reverse((cost, i))) = heap.pop() {
+        let state = states[i].clone();
+        if paths.distances.contains_key(&state) || tentative.get(&state).is_some_and(|(best, _)| *best < cost) {
+            continue;
+        }
+        let (_, predecessor) = tentative.remove(&state).expect("Queued states are tentative");
+        if let Some(predecessor) = predecessor {
+            paths.predecessors.insert(state.clone(), predecessor);
+        }
+        paths.distances.insert(state.clone(), cost);
+        if is_goal(&state) {
+            paths.goal = Some(state);
+            break;
+        }
+        for (next, step) in successors(&state) {
+            let next_cost = cost + step;
+            if paths.distances.contains_key(&next) || tentative.get(&next).is_some_and(|(best, _)| *best <= next_cost) {
+                continue;
+            }
+            tentative.insert(next.clone(), (next_cost, Some(state.clone())));
+            states.push(next);
+            heap.push(Reverse((next_cost, states.len() - 1)));
+        }
+    }
+    paths
+}
+
+// Over a graph whose edge weights are the costs.
+pub fn dijkstra<N, E, G>(graph: &Graph<N, E>, start: NodeId, is_goal: G) -> Paths<NodeId, E>
+    where N: Clone + Eq + Hash,
+          E: Copy + Ord + Add<Output = E> + Default,
+          G: FnMut(&NodeId) -> bool
+{
+    dijkstra_by(start, |&node| graph.edges_from(node).to_vec(), is_goal)
+}
+
+// Hooks a traversal calls as it goes. Depth counts steps from the nearest start for a
+// breadth-first search, and steps down the current path for a depth-first one.
+pub trait Visitor<S> {
+    // When state is first reached. Returning false leaves its successors unexplored.
+    fn discover(&mut self, _state: &S, _depth: usize) -> bool { true }
+    // Once every successor of state has been discovered, or for depth-first, fully explored.
+    fn finish(&mut self, _state: &S) {}
+}
+
+impl<S> Visitor<S> for () {}
+
+// A visitor that only wants to hear about discoveries.
+pub struct OnDiscover<F>(pub F);
+
+impl<S, F: FnMut(&S, usize) -> bool> Visitor<S> for OnDiscover<F> {
+    fn discover(&mut self, state: &S, depth: usize) -> bool { (self.0)(state, depth) }
+}
+
+// Where a traversal records what it has seen, so a grid search can use a BitGrid rather than
+// hashing every point.
+pub trait VisitedSet<S> {
+    // False if state was already there.
+    fn insert(&mut self, state: &S) -> bool;
+}
+
+impl<S: Clone + Eq + Hash> VisitedSet<S> for HashSet<S> {
+    fn insert(&mut self, state: &S) -> bool { HashSet::insert(self, state.clone()) }
+}
+
+impl<S: Clone + Ord> VisitedSet<S> for BTreeSet<S> {
+    fn insert(&mut self, state: &S) -> bool { BTreeSet::insert(self, state.clone()) }
+}
+
+// Indexed by NodeId, and as long as the graph has nodes.
+impl VisitedSet<NodeId> for Vec<bool> {
+    fn insert(&mut self, state: &NodeId) -> bool { !std::mem::replace(&mut self[*state], true) }
+}
+
+impl VisitedSet<Point> for BitGrid {
+    fn insert(&mut self, state: &Point) -> bool { BitGrid::insert(self, state) }
+}
+
+pub fn bfs_with<S, F, I, V, Vis>(starts: impl IntoIterator<Item = S>, mut successors: F, visited: &mut V, visitor: &mut Vis)
+    where F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          V: VisitedSet<S>,
+          Vis: Visitor<S>
+{
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if visited.insert(&start) && visitor.discover(&start, 0) {
+            queue.push_back((start, 0));
+        }
+    }
+    while let Some((state, depth)) = queue.pop_front() {
+        for next in successors(&state) {
+            if visited.insert(&next) && visitor.discover(&next, depth + 1) {
+                queue.push_back((next, depth + 1));
+            }
+        }
+        visitor.finish(&state);
+    }
+}
+
+// Everything reachable from start.
+pub fn bfs_by<S, F, I, Vis>(start: S, successors: F, visitor: &mut Vis) -> HashSet<S>
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          Vis: Visitor<S>
+{
+    let mut visited = HashSet::new();
+    bfs_with([start], successors, &mut visited, visitor);
+    visited
+}
+
+// Iterative, so deep paths can't overflow the call stack.
+pub fn dfs_with<S, F, I, V, Vis>(starts: impl IntoIterator<Item = S>, mut successors: F, visited: &mut V, visitor: &mut Vis)
+    where F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          V: VisitedSet<S>,
+          Vis: Visitor<S>
+{
+    for start in starts {
+        if !(visited.insert(&start) && visitor.discover(&start, 0)) {
+            continue;
+        }
+        let next = successors(&start).into_iter();
+        let mut stack = vec![(start, next)];
+        while let Some((_, next)) = stack.last_mut() {
+            match next.next() {
+                Some(state) => {
+                    if visited.insert(&state) && visitor.discover(&state, stack.len()) {
+                        let next = successors(&state).into_iter();
+                        stack.push((state, next));
+                    }
+                },
+                None => {
+                    if let Some((state, _)) = stack.pop() {
+                        visitor.finish(&state);
+                    }
+                },
+            }
+        }
+    }
+}
+
+pub fn dfs_by<S, F, I, Vis>(start: S, successors: F, visitor: &mut Vis) -> HashSet<S>
+    where S: Clone + Eq + Hash,
+          F: FnMut(&S) -> I,
+          I: IntoIterator<Item = S>,
+          Vis: Visitor<S>
+{
+    let mut visited = HashSet::new();
+    dfs_with([start], successors, &mut visited, visitor);
+    visited
+}
+
+// Which nodes of the graph are reachable from start, indexed by NodeId.
+pub fn bfs<N: Clone + Eq + Hash, E: Clone, Vis: Visitor<NodeId>>(graph: &Graph<N, E>, start: NodeId, visitor: &mut Vis) -> Vec<bool> {
+    let mut visited = vec![false; graph.len()];
+    bfs_with([start], |&node| graph.neighbors(node), &mut visited, visitor);
+    visited
+}
+
+pub fn dfs<N: Clone + Eq + Hash, E: Clone, Vis: Visitor<NodeId>>(graph: &Graph<N, E>, start: NodeId, visitor: &mut Vis) -> Vec<bool> {
+    let mut visited = vec![false; graph.len()];
+    dfs_with([start], |&node| graph.neighbors(node), &mut visited, visitor);
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direction::Direction;
+    use crate::grid::{AoCGrid, Grid2D, Point};
+
+    #[test]
+    fn graph() {
+        let mut g: Graph<char, u32> = Graph::directed();
+        for (from, to, weight) in [('a', 'b', 7), ('a', 'c', 9), ('a', 'f', 14), ('b', 'c', 10), ('b', 'd', 15), ('c', 'd', 11), ('c', 'f', 2), ('d', 'e', 6), ('f', 'e', 9)] {
+            g.add_edge(from, to, weight);
+        }
+        let id = |c| g.id(&c).unwrap();
+        let paths = dijkstra(&g, id('a'), |_| false);
+        assert_eq!(Some(&20), paths.distances.get(&id('e')));
+        assert_eq!(Some(&11), paths.distances.get(&id('f')));
+        assert_eq!(Some(vec!['a', 'c', 'f', 'e']), paths.path_to(&id('e')).map(|p| p.into_iter().map(|n| *g.key(n)).collect()));
+        let to_d = dijkstra(&g, id('a'), |n| *n == id('d'));
+        assert_eq!((Some(id('d')), Some(&20)), (to_d.goal, to_d.distances.get(&id('d'))));
+        assert!(!to_d.distances.contains_key(&id('e')));
+        assert_eq!(None, dijkstra(&g, id('e'), |_| false).path_to(&id('a')));
+    }
+
+    // Records the order of every hook call.
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl Visitor<NodeId> for Log {
+        fn discover(&mut self, state: &NodeId, depth: usize) -> bool {
+            self.0.push(format!("+{}@{}", state, depth));
+            true
+        }
+        fn finish(&mut self, state: &NodeId) {
+            self.0.push(format!("-{}", state));
+        }
+    }
+
+    #[test]
+    fn traversals() {
+        let mut g: Graph<u32> = Graph::directed();
+        for (from, to) in [(0, 1), (0, 2), (1, 3), (2, 3), (3, 0), (4, 0)] {
+            g.add_edge(from, to, ());
+        }
+        let mut log = Log::default();
+        assert_eq!(vec![true, true, true, true, false], bfs(&g, 0, &mut log));
+        assert_eq!("+0@0 +1@1 +2@1 -0 +3@2 -1 -2 -3", log.0.join(" "));
+        let mut log = Log::default();
+        assert_eq!(vec![true, true, true, true, false], dfs(&g, 0, &mut log));
+        assert_eq!("+0@0 +1@1 +3@2 -3 -1 +2@1 -2 -0", log.0.join(" "));
+        assert_eq!(5, dfs(&g, 4, &mut ()).into_iter().filter(|v| *v).count());
+        // Pruned at depth 1, so 3 is never reached.
+        let near = bfs(&g, 0, &mut OnDiscover(|_: &NodeId, depth| depth < 1));
+        assert_eq!(vec![true, true, true, false, false], near);
+    }
+
+    #[test]
+    fn flood_fill() {
+        let grid = AoCGrid::new("..#..\n..#..\n###..\n.....");
+        let open = |p: &Point| grid.get(p) == Some(".");
+        let successors = |p: &Point| Direction::ALL.into_iter().filter_map(|d| d.step(p)).filter(open).collect::<Vec<_>>();
+        assert_eq!(4, bfs_by(Point { x: 0, y: 0 }, successors, &mut ()).len());
+        let mut visited = BitGrid::with_size_of(&grid);
+        let mut furthest = 0;
+        bfs_with([Point { x: 4, y: 0 }], successors, &mut visited, &mut OnDiscover(|_: &Point, depth| { furthest = furthest.max(depth); true }));
+        assert_eq!((11, 7), (visited.count(), furthest));
+        assert_eq!(11, dfs_by(Point { x: 4, y: 3 }, successors, &mut ()).len());
+        let mut deep = BTreeSet::new();
+        dfs_with(0..1, |n: &u32| (*n < 100_000).then_some(n + 1), &mut deep, &mut ());
+        assert_eq!(100_001, deep.len());
+    }
+
+    #[test]
+    fn implicit_grid() {
+        // Each step costs the digit stepped onto.
+        let input = "1163751\n1381373\n2136511\n3694931\n7463417";
+        let grid = AoCGrid::new(input);
+        let cost = |p: &Point| grid.get(p).and_then(|c| c.parse::<u32>().ok());
+        let end = Point { x: grid.width() - 1, y: grid.height() - 1 };
+        let successors = |p: &Point| Direction::ALL.into_iter()
+            .filter_map(|d| d.step(p))
+            .filter_map(|n| Some((n, cost(&n)?)))
+            .collect::<Vec<_>>();
+        let paths = dijkstra_by(Point { x: 0, y: 0 }, successors, |p| *p == end);
+        assert_eq!(Some(end), paths.goal);
+        assert_eq!(Some(&28), paths.distances.get(&end));
+        assert_eq!(Some(28), paths.path_to(&end).unwrap().iter().skip(1).map(cost).sum::<Option<u32>>());
+    }
+}
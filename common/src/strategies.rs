@@ -0,0 +1,98 @@
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use proptest::sample::{select, Index};
+
+// Structurally valid puzzle inputs, as text, for property testing each day's solvers against a
+// slow reference. Numbers are kept small enough that the references can brute force them.
+
+pub const ALMANAC_CATEGORIES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+
+// Rectangular and ASCII, mostly dots, so runs of digits stay short.
+pub fn schematic(max_width: usize, max_height: usize) -> impl Strategy<Value = String> {
+    (1..=max_width, 1..=max_height)
+        .prop_flat_map(|(width, height)| vec(vec(schematic_cell(), width), height))
+        .prop_map(|rows| rows.into_iter().map(|row| row.into_iter().collect::<String>()).collect::<Vec<_>>().join("\n"))
+}
+
+fn schematic_cell() -> impl Strategy<Value = char> {
+    prop_oneof![
+        6 => Just('.'),
+        3 => select(('0'..='9').collect::<Vec<_>>()),
+        1 => select(vec!['*', '*', '#', '+', '$', '/', '=', '%', '@', '&', '-']),
+    ]
+}
+
+// Each card matches at most as many cards as come after it, so copies never run off the end.
+pub fn card_set(max_cards: usize) -> impl Strategy<Value = String> {
+    vec((btree_set(1..100u64, 1..10), btree_set(100..200u64, 0..10), any::<Index>(), any::<Index>()), 1..=max_cards)
+        .prop_map(|cards| {
+            let count = cards.len();
+            cards.into_iter().enumerate().map(|(i, (winning, others, matches, rotation))| {
+                let winning: Vec<u64> = winning.into_iter().collect();
+                let matches = matches.index(winning.len().min(count - i - 1) + 1);
+                let mut chosen: Vec<u64> = others.into_iter().chain(winning.iter().copied().take(matches)).collect();
+                if !chosen.is_empty() {
+                    let at = rotation.index(chosen.len());
+                    chosen.rotate_left(at);
+                }
+                format!("Card {:>3}: {} | {}", i + 1, spaced(&winning), spaced(&chosen))
+            }).collect::<Vec<_>>().join("\n")
+        })
+}
+
+// Seeds in (start, length) pairs, then a map for each step from seed to location whose source
+// ranges don't overlap and are listed in no particular order. Some maps have no ranges at all.
+pub fn almanac(max_seed_ranges: usize) -> impl Strategy<Value = String> {
+    (vec((0..200u64, 1..20u64), 1..=max_seed_ranges), vec(map_ranges(), ALMANAC_CATEGORIES.len() - 1))
+        .prop_map(|(seeds, maps)| {
+            let mut text = format!("seeds:{}", seeds.iter().map(|(start, length)| format!(" {} {}", start, length)).collect::<String>());
+            for (pair, ranges) in ALMANAC_CATEGORIES.windows(2).zip(maps) {
+                text += &format!("\n\n{}-to-{} map:", pair[0], pair[1]);
+                for (destination, source, length) in ranges {
+                    text += &format!("\n{} {} {}", destination, source, length);
+                }
+            }
+            text + "\n"
+        })
+}
+
+// (destination, source, length), built from the gap before each source range.
+fn map_ranges() -> impl Strategy<Value = Vec<(u64, u64, u64)>> {
+    vec((0..40u64, 1..40u64, 0..400u64), 0..6)
+        .prop_map(|specs| {
+            let mut start = 0;
+            specs.into_iter().map(|(gap, length, destination)| {
+                start += gap;
+                let range = (destination, start, length);
+                start += length;
+                range
+            }).collect()
+        })
+        .prop_shuffle()
+}
+
+fn spaced(numbers: &[u64]) -> String {
+    numbers.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::TestRunner;
+
+    #[test]
+    fn shapes() {
+        let mut runner = TestRunner::deterministic();
+        for _ in 0..50 {
+            let text = schematic(8, 5).new_tree(&mut runner).unwrap().current();
+            let widths: Vec<usize> = text.lines().map(str::len).collect();
+            assert!(widths.iter().all(|w| *w == widths[0] && *w <= 8) && widths.len() <= 5, "{:?}", text);
+            let cards = card_set(6).new_tree(&mut runner).unwrap().current();
+            assert!(cards.lines().all(|l| l.starts_with("Card") && l.matches('|').count() == 1), "{:?}", cards);
+            let almanac = almanac(3).new_tree(&mut runner).unwrap().current();
+            assert_eq!(7, almanac.matches(" map:").count());
+            assert_eq!(1, almanac.lines().next().unwrap().split_ascii_whitespace().count() % 2);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TokenKind {
+    Ident,
+    Number,
+    Punct,
+}
+
+// at is the byte offset of the token in the line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub at: usize,
+}
+
+impl Token<'_> {
+    pub fn is_punct(&self, c: char) -> bool {
+        self.kind == TokenKind::Punct && self.text.starts_with(c)
+    }
+}
+
+// Splits a line into identifiers (a letter or '_' then letters, digits and '_'), runs of digits,
+// and single punctuation characters, skipping whitespace. Symbol-heavy formats such as workflow
+// rules ("a<2006:qkq") read more easily from tokens than by scanning characters.
+pub fn tokenize(line: &str) -> impl Iterator<Item = Token<'_>> {
+    let mut chars = line.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        let (at, c) = chars.next()?;
+        let (kind, continues): (TokenKind, fn(char) -> bool) = if c.is_alphabetic() || c == '_' {
+            (TokenKind::Ident, |c| c.is_alphanumeric() || c == '_')
+        }
+        else if c.is_ascii_digit() {
+            (TokenKind::Number, |c| c.is_ascii_digit())
+        }
+        else {
+            (TokenKind::Punct, |_| false)
+        };
+        let mut end = at + c.len_utf8();
+        while let Some((i, c)) = chars.next_if(|(_, c)| continues(*c)) {
+            end = i + c.len_utf8();
+        }
+        Some(Token { kind, text: &line[at..end], at })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let tokens: Vec<(TokenKind, &str, usize)> = tokenize("px{a<2006:qkq,rfg}").map(|t| (t.kind, t.text, t.at)).collect();
+        assert_eq!(vec![
+            (TokenKind::Ident, "px", 0), (TokenKind::Punct, "{", 2), (TokenKind::Ident, "a", 3), (TokenKind::Punct, "<", 4),
+            (TokenKind::Number, "2006", 5), (TokenKind::Punct, ":", 9), (TokenKind::Ident, "qkq", 10), (TokenKind::Punct, ",", 13),
+            (TokenKind::Ident, "rfg", 14), (TokenKind::Punct, "}", 17),
+        ], tokens);
+        let texts: Vec<&str> = tokenize("  Game 12:\t3 blue_ish; é->x2  ").map(|t| t.text).collect();
+        assert_eq!(vec!["Game", "12", ":", "3", "blue_ish", ";", "é", "-", ">", "x2"], texts);
+        assert!(tokenize("a -> b").nth(1).unwrap().is_punct('-'));
+        assert_eq!(0, tokenize("   ").count());
+    }
+}
@@ -0,0 +1,65 @@
+use std::fs::read_to_string;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use common::progress::Progress;
+use five::{seed_count, solve_two_with, Almanac, Solver};
+
+const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+fn bench_input(c: &mut Criterion, label: &str, almanac: &Almanac, solvers: &[Solver]) {
+    let mut group = c.benchmark_group(label);
+    group.sample_size(10);
+    for solver in solvers {
+        group.bench_function(solver.name(), |b| b.iter(|| {
+            let progress = Progress::new("seeds", seed_count(almanac)).hidden();
+            solve_two_with(almanac, *solver, &progress)
+        }));
+    }
+    group.finish();
+}
+
+fn strategies(c: &mut Criterion) {
+    let example: Almanac = EXAMPLE.parse().expect("The example parses");
+    bench_input(c, "example", &example, &Solver::ALL);
+    // The brute force takes minutes on a real almanac, so it is only timed on the example.
+    if let Ok(input) = read_to_string("input.txt") {
+        let almanac: Almanac = input.parse().expect("input.txt parses");
+        bench_input(c, "input", &almanac, &[Solver::Interval, Solver::Inverse]);
+    }
+}
+
+criterion_group!(benches, strategies);
+criterion_main!(benches);
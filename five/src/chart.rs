@@ -0,0 +1,67 @@
+use std::error::Error;
+
+use plotters::prelude::*;
+
+use common::interval::Interval;
+
+use crate::{Almanac, Range, Value};
+
+// The seed ranges from part two, lowest first.
+fn seed_ranges(almanac: &Almanac) -> Vec<Interval<Value>> {
+    let mut ranges: Vec<Interval<Value>> = almanac.seed_ranges().filter(|r| !r.is_empty()).collect();
+    ranges.sort();
+    ranges
+}
+
+// Draws the composed seed-to-location map over the span of the seed ranges as an SVG. The seed
+// ranges are shaded and the lowest location reachable from them is marked.
+pub fn render_svg(almanac: &Almanac) -> Result<String, Box<dyn Error>> {
+    let seeds = seed_ranges(almanac);
+    let span = match (seeds.first(), seeds.iter().map(|r| r.end).max()) {
+        (Some(first), Some(end)) => Interval::new(first.start, end),
+        _ => return Err("No seed ranges to plot".into()),
+    };
+    let pieces = almanac.composed.pieces(span);
+    let top = pieces.iter().map(|p| p.destination_start + p.length).max().unwrap_or(1);
+    // Each piece maps onto a straight line, so its lowest location is at its first seed.
+    let best: Option<Range> = seeds.iter()
+        .flat_map(|r| almanac.composed.pieces(*r))
+        .min_by_key(|p| p.destination_start);
+
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (1024, 768)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("seed to location", ("sans-serif", 24))
+            .margin(10)
+            .x_label_area_size(40)
+            .y_label_area_size(100)
+            .build_cartesian_2d(span.start as f64..span.end as f64, 0f64..top as f64)?;
+        chart.configure_mesh().x_desc("seed").y_desc("location").draw()?;
+        chart.draw_series(seeds.iter().map(|r| Rectangle::new([(r.start as f64, 0.0), (r.end as f64, top as f64)], BLUE.mix(0.15).filled())))?;
+        chart.draw_series(pieces.iter().map(|p| {
+            let (x, y, length) = (p.source_start as f64, p.destination_start as f64, p.length as f64);
+            PathElement::new(vec![(x, y), (x + length, y + length)], RED.stroke_width(2))
+        }))?;
+        chart.draw_series(best.iter().map(|p| Circle::new((p.source_start as f64, p.destination_start as f64), 6, GREEN.filled())))?;
+        root.present()?;
+    }
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_the_best_seed() {
+        let almanac: Almanac = "seeds: 10 5 30 5\n\nseed-to-location map:\n100 10 5\n7 32 10".parse().unwrap();
+        assert_eq!(vec![Interval::new(10, 15), Interval::new(30, 35)], seed_ranges(&almanac));
+        let svg = render_svg(&almanac).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(1, svg.matches("<circle").count());
+        let almanac: Almanac = "seeds: 10 5 30 0\n\nseed-to-location map:\n100 10 5".parse().unwrap();
+        assert_eq!(vec![Interval::new(10, 15)], seed_ranges(&almanac));
+    }
+}
@@ -0,0 +1,1004 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::str::FromStr;
+use rayon::prelude::*;
+
+use std::sync::Mutex;
+
+use common::diagnostic::{Diagnostic, ToDiagnostic};
+use common::error::AocError;
+use common::interval::Interval;
+use common::parse::ParseMode;
+use common::progress::Progress;
+
+#[cfg(feature = "chart")]
+pub mod chart;
+
+// Everything is checked to fit at parse time, so mapping a value can never overflow. Inputs
+// with ranges that run past u64::MAX need the wider type.
+#[cfg(not(feature = "u128"))]
+pub type Value = u64;
+#[cfg(feature = "u128")]
+pub type Value = u128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Range {
+    pub source_start: Value,
+    pub destination_start: Value,
+    pub length: Value,
+}
+
+impl Range {
+    fn source(&self) -> Interval<Value> {
+        Interval::with_length(self.source_start, self.length)
+    }
+
+    // Only meaningful for values inside source().
+    fn map(&self, input: Value) -> Value {
+        self.destination_start + (input - self.source_start)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseRangeError {
+    FieldCount(usize),
+    InvalidInteger(String),
+    TooLarge,
+}
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRangeError::FieldCount(n) => write!(f, "expected 3 numbers, found {}", n),
+            ParseRangeError::InvalidInteger(s) => write!(f, "{:?} is not a valid number", s),
+            ParseRangeError::TooLarge => write!(f, "range runs past {} (try building with --features u128)", Value::MAX),
+        }
+    }
+}
+
+impl FromStr for Range {
+    type Err = ParseRangeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<Value> = s.split_ascii_whitespace()
+            .map(|s| s.parse().map_err(|_| ParseRangeError::InvalidInteger(s.to_string())))
+            .collect::<Result<_, _>>()?;
+        match parts[..] {
+            [destination_start, source_start, length] => {
+                if source_start.checked_add(length).is_none() || destination_start.checked_add(length).is_none() {
+                    return Err(ParseRangeError::TooLarge);
+                }
+                Ok(Range { source_start, destination_start, length })
+            },
+            _ => Err(ParseRangeError::FieldCount(parts.len())),
+        }
+    }
+}
+
+pub trait Transformer {
+    fn transform(&self, input: Value) -> Value;
+    // Maps every value of each (start, length) range at once; the output ranges are unordered.
+    fn transform_ranges(&self, ranges: Vec<(Value, Value)>) -> Vec<(Value, Value)>;
+    fn inverse(&self) -> InverseTransformer;
+}
+
+// Maps outputs back to every input that produces them. Several inputs can share an output,
+// so the pieces may overlap.
+#[derive(Debug)]
+pub struct InverseTransformer {
+    ranges: Vec<Range>,
+}
+
+impl InverseTransformer {
+    fn new(pieces: Vec<Range>) -> Self {
+        let mut ranges: Vec<Range> = pieces.into_iter()
+            .map(|r| Range { source_start: r.destination_start, destination_start: r.source_start, length: r.length })
+            .collect();
+        ranges.sort_by_key(|r| r.source_start);
+        Self { ranges }
+    }
+
+    pub fn sources(&self, output: Value) -> impl Iterator<Item = Value> + '_ {
+        let end = self.ranges.partition_point(|r| r.source_start <= output);
+        self.ranges[..end].iter()
+            .filter(move |r| r.source().contains(output))
+            .map(move |r| r.map(output))
+    }
+}
+
+#[derive(Debug)]
+pub struct StageTransformer {
+    // Categories from the "source-to-destination map:" title; empty for unnamed stages.
+    source: String,
+    destination: String,
+    pub ranges: Vec<Range>,
+    // The ranges cut down so none overlap, in ascending source order. Where listed ranges
+    // overlap, the first listed wins, as it would reading the map top to bottom.
+    lookup: Vec<Range>,
+}
+
+impl StageTransformer {
+    fn new(ranges: Vec<Range>) -> Self {
+        Self::named("", "", ranges)
+    }
+
+    fn named(source: &str, destination: &str, mut ranges: Vec<Range>) -> Self {
+        let lookup = first_listed_wins(&ranges);
+        ranges.sort_by(|a, b| a.source_start.partial_cmp(&b.source_start).unwrap() );
+        Self { source: source.to_string(), destination: destination.to_string(), ranges, lookup }
+    }
+
+    pub fn name(&self) -> String {
+        format!("{}-to-{}", self.source, self.destination)
+    }
+
+    // The lookup pieces are disjoint, so only the last one starting at or below input can hold it.
+    fn containing(&self, input: Value) -> Option<&Range> {
+        let pp = self.lookup.partition_point(|r| r.source_start <= input);
+        pp.checked_sub(1).and_then(|idx| self.lookup.get(idx)).filter(|r| r.source().contains(input))
+    }
+
+    // Splits the interval where the mapping changes, in ascending source order.
+    fn pieces(&self, interval: Interval<Value>) -> Vec<Range> {
+        let mut pieces = Vec::new();
+        let mut rest = interval;
+        while !rest.is_empty() {
+            let piece = match self.containing(rest.start).and_then(|range| range.source().intersect(&rest).map(|overlap| (range, overlap))) {
+                Some((range, overlap)) => Range {
+                    source_start: overlap.start,
+                    destination_start: range.map(overlap.start),
+                    length: overlap.len(),
+                },
+                // Unmapped values pass through unchanged up to where the next range begins.
+                None => {
+                    let pp = self.lookup.partition_point(|r| r.source_start <= rest.start);
+                    let (unmapped, _) = rest.split_at(self.lookup.get(pp).map_or(rest.end, |next| next.source_start));
+                    Range { source_start: unmapped.start, destination_start: unmapped.start, length: unmapped.len() }
+                }
+            };
+            rest = rest.split_at(piece.source().end).1;
+            pieces.push(piece);
+        }
+        pieces
+    }
+}
+
+// Each range less whatever earlier ranges already cover, in ascending source order.
+fn first_listed_wins(ranges: &[Range]) -> Vec<Range> {
+    let mut sorted: Vec<Range> = ranges.iter().filter(|r| r.length > 0).cloned().collect();
+    sorted.sort_by_key(|r| r.source_start);
+    if sorted.windows(2).all(|w| !w[0].source().overlaps(&w[1].source())) {
+        return sorted;
+    }
+    let mut claimed: Vec<Range> = Vec::new();
+    for range in ranges.iter().filter(|r| r.length > 0) {
+        let mut rest = vec![range.source()];
+        for earlier in &claimed {
+            rest = rest.into_iter().flat_map(|r| {
+                let (below, from) = r.split_at(earlier.source_start);
+                [below, from.split_at(earlier.source().end).1]
+            }).filter(|r| !r.is_empty()).collect();
+        }
+        claimed.extend(rest.into_iter().map(|r| Range { source_start: r.start, destination_start: range.map(r.start), length: r.len() }));
+    }
+    claimed.sort_by_key(|r| r.source_start);
+    claimed
+}
+
+// Problems with a stage's ranges that the puzzle never exercises. Overlaps are the serious
+// one: which range wins depends on the order they were listed in.
+#[derive(Debug, Default, PartialEq)]
+pub struct StageDiagnosis {
+    name: String,
+    overlaps: Vec<Interval<Value>>,
+    // Unmapped stretches between the lowest and highest mapped source values.
+    gaps: Vec<Interval<Value>>,
+    // Ranges that map onto themselves, so could be left out.
+    identity: Vec<Interval<Value>>,
+}
+
+impl fmt::Display for StageDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.overlaps.is_empty() && self.gaps.is_empty() && self.identity.is_empty() {
+            return write!(f, ": ok");
+        }
+        for (label, intervals) in [("overlap", &self.overlaps), ("gap", &self.gaps), ("identity", &self.identity)] {
+            for interval in intervals {
+                write!(f, "\n  {} {}..{}", label, interval.start, interval.end)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StageTransformer {
+    fn diagnose(&self) -> StageDiagnosis {
+        let mut diagnosis = StageDiagnosis { name: self.name(), ..Default::default() };
+        let sources: Vec<Interval<Value>> = self.ranges.iter().map(|r| r.source()).filter(|s| !s.is_empty()).collect();
+        for (i, a) in sources.iter().enumerate() {
+            // Sorted by start, so only the ranges that begin before this one ends can overlap it.
+            diagnosis.overlaps.extend(sources[i + 1..].iter().take_while(|b| b.start < a.end).filter_map(|b| a.intersect(b)));
+        }
+        let mut covered: Option<Value> = None;
+        for source in &sources {
+            if let Some(end) = covered.filter(|end| *end < source.start) {
+                diagnosis.gaps.push(Interval::new(end, source.start));
+            }
+            covered = Some(covered.map_or(source.end, |end| end.max(source.end)));
+        }
+        diagnosis.identity = self.ranges.iter().filter(|r| r.length > 0 && r.source_start == r.destination_start).map(|r| r.source()).collect();
+        diagnosis
+    }
+}
+
+impl Transformer for StageTransformer {
+    fn transform(&self, input: Value) -> Value {
+        self.containing(input).map_or(input, |range| range.map(input))
+    }
+
+    fn transform_ranges(&self, ranges: Vec<(Value, Value)>) -> Vec<(Value, Value)> {
+        ranges.into_iter()
+            .flat_map(|(start, length)| self.pieces(Interval::with_length(start, length)))
+            .map(|piece| (piece.destination_start, piece.length))
+            .collect()
+    }
+
+    fn inverse(&self) -> InverseTransformer {
+        InverseTransformer::new(self.pieces(Interval::new(0, Value::MAX)))
+    }
+}
+
+#[derive(Debug)]
+struct AlmanacTransformer {
+    stage_transformers: Vec<StageTransformer>,
+}
+
+impl Transformer for AlmanacTransformer {
+    fn transform(&self, input: Value) -> Value {
+        let mut result = input;
+        for stage_transformer in &self.stage_transformers {
+            result = stage_transformer.transform(result);
+        }
+        result
+    }
+
+    fn transform_ranges(&self, ranges: Vec<(Value, Value)>) -> Vec<(Value, Value)> {
+        self.stage_transformers.iter().fold(ranges, |ranges, stage_transformer| stage_transformer.transform_ranges(ranges))
+    }
+
+    fn inverse(&self) -> InverseTransformer {
+        self.compose().inverse()
+    }
+}
+
+impl AlmanacTransformer {
+    // Folds every stage into one map. Unmapped stretches are kept as identity pieces so the
+    // result covers the whole domain.
+    fn compose(&self) -> StageTransformer {
+        let identity = vec![Range { source_start: 0, destination_start: 0, length: Value::MAX }];
+        let ranges = self.stage_transformers.iter().fold(identity, |composed, stage_transformer| {
+            composed.iter().flat_map(|outer| {
+                stage_transformer.pieces(Interval::with_length(outer.destination_start, outer.length)).into_iter().map(move |inner| Range {
+                    source_start: outer.source_start + (inner.source_start - outer.destination_start),
+                    destination_start: inner.destination_start,
+                    length: inner.length,
+                })
+            }).collect()
+        });
+        match (self.stage_transformers.first(), self.stage_transformers.last()) {
+            (Some(first), Some(last)) => StageTransformer::named(&first.source, &last.destination, ranges),
+            _ => StageTransformer::new(ranges),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Almanac {
+    transformer: AlmanacTransformer,
+    composed: StageTransformer,
+    seeds: Vec<Value>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AlmanacErrorKind {
+    MissingSeeds,
+    InvalidSeed(String),
+    NoSeeds,
+    OddSeedCount(usize),
+    EmptySeedRanges,
+    MissingTitle,
+    InvalidTitle(String),
+    DuplicateSource(String),
+    MissingStage(String),
+    CyclicMaps(Vec<String>),
+    InvalidRange(ParseRangeError),
+    Read(String),
+}
+
+// Lines are numbered from 1, as an editor would show them.
+#[derive(Debug, PartialEq)]
+pub struct ParseAlmanacError {
+    section: String,
+    line: usize,
+    kind: AlmanacErrorKind,
+}
+
+impl fmt::Display for AlmanacErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlmanacErrorKind::MissingSeeds => write!(f, "expected a \"seeds:\" line"),
+            AlmanacErrorKind::InvalidSeed(s) => write!(f, "{:?} is not a valid seed", s),
+            AlmanacErrorKind::NoSeeds => write!(f, "no seeds after \"seeds:\""),
+            AlmanacErrorKind::OddSeedCount(n) => write!(f, "{} seed numbers, which don't pair up into starts and lengths", n),
+            AlmanacErrorKind::EmptySeedRanges => write!(f, "every seed range is empty"),
+            AlmanacErrorKind::MissingTitle => write!(f, "expected an \"X-to-Y map:\" title"),
+            AlmanacErrorKind::InvalidTitle(s) => write!(f, "{:?} does not name a source and destination", s),
+            AlmanacErrorKind::DuplicateSource(s) => write!(f, "a second map from {}", s),
+            AlmanacErrorKind::MissingStage(s) => write!(f, "no map from {} on the way to location", s),
+            AlmanacErrorKind::CyclicMaps(categories) => write!(f, "the maps go round in a loop: {} -> {}", categories.join(" -> "), categories[0]),
+            AlmanacErrorKind::InvalidRange(e) => write!(f, "invalid range: {}", e),
+            AlmanacErrorKind::Read(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl fmt::Display for ParseAlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} ({}): {}", self.line, self.section, self.kind)
+    }
+}
+
+impl Error for ParseAlmanacError {}
+
+impl From<ParseAlmanacError> for AocError {
+    fn from(e: ParseAlmanacError) -> Self {
+        AocError::Parse(Diagnostic::at_line(e.line, &format!("{} ({})", e.kind, e.section)))
+    }
+}
+
+impl ToDiagnostic for ParseAlmanacError {
+    fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        let message = format!("{} ({})", self.kind, self.section);
+        match &self.kind {
+            AlmanacErrorKind::InvalidSeed(s) => Diagnostic::at_token(source, self.line, s, &message, "not a seed"),
+            AlmanacErrorKind::InvalidTitle(s) => Diagnostic::at_token(source, self.line, s, &message, "not \"X-to-Y\""),
+            AlmanacErrorKind::DuplicateSource(s) => Diagnostic::at_token(source, self.line, s, &message, "already mapped"),
+            AlmanacErrorKind::InvalidRange(_) => Diagnostic::whole_line(source, self.line, &message, "not a range"),
+            _ => Diagnostic::whole_line(source, self.line, &message, "here"),
+        }
+    }
+}
+
+impl FromStr for Almanac {
+    type Err = ParseAlmanacError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Almanac::from_reader(s.as_bytes())
+    }
+}
+
+impl Almanac {
+    // Builds the almanac a line at a time, so piped input never has to be held in memory whole.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, ParseAlmanacError> {
+        Almanac::from_reader_with(reader, ParseMode::global())
+    }
+
+    // Lenient mode ignores trailing whitespace, blank lines before the seeds, and lines in or
+    // between maps that are neither ranges nor titles.
+    pub fn from_reader_with<R: BufRead>(reader: R, mode: ParseMode) -> Result<Self, ParseAlmanacError> {
+        let lenient = mode == ParseMode::Lenient;
+        let error = |section: &str, line: usize, kind: AlmanacErrorKind| ParseAlmanacError { section: section.to_string(), line, kind };
+        let read = |line: usize, text: io::Result<String>| text
+            .map(|t| if lenient { t.trim_end().to_string() } else { t })
+            .map_err(|e| error("input", line, AlmanacErrorKind::Read(e.to_string())));
+        let mut input_lines = reader.lines().enumerate().map(|(i, l)| (i + 1, l));
+        let (seeds_line, first_line) = loop {
+            match input_lines.next() {
+                Some((line, text)) => {
+                    let text = read(line, text)?;
+                    if !(lenient && text.is_empty()) {
+                        break (line, Some(text));
+                    }
+                },
+                None => break (1, None),
+            }
+        };
+        let seeds_input = first_line.as_deref()
+            .and_then(|l| l.strip_prefix("seeds:"))
+            .ok_or_else(|| error("seeds", seeds_line, AlmanacErrorKind::MissingSeeds))?;
+        let seeds: Vec<Value> = seeds_input.split_ascii_whitespace()
+            .map(|s| s.parse().map_err(|_| error("seeds", seeds_line, AlmanacErrorKind::InvalidSeed(s.to_string()))))
+            .collect::<Result<_, _>>()?;
+        // Both parts need at least one seed, and part two reads them in pairs.
+        if seeds.is_empty() {
+            return Err(error("seeds", seeds_line, AlmanacErrorKind::NoSeeds));
+        }
+        if !seeds.len().is_multiple_of(2) {
+            return Err(error("seeds", seeds_line, AlmanacErrorKind::OddSeedCount(seeds.len())));
+        }
+        if seeds.chunks_exact(2).all(|c| c[1] == 0) {
+            return Err(error("seeds", seeds_line, AlmanacErrorKind::EmptySeedRanges));
+        }
+
+        // Keyed by source category, with the line of the map's title.
+        let mut stages: HashMap<String, (usize, StageTransformer)> = HashMap::new();
+        // (title line, title, source, destination, ranges so far)
+        let mut section: Option<(usize, String, String, String, Vec<Range>)> = None;
+        let mut finish = |section: Option<(usize, String, String, String, Vec<Range>)>| {
+            if let Some((line, name, source, destination, ranges)) = section {
+                if stages.contains_key(&source) {
+                    return Err(error(&name, line, AlmanacErrorKind::DuplicateSource(source)));
+                }
+                stages.insert(source.clone(), (line, StageTransformer::named(&source, &destination, ranges)));
+            }
+            Ok(())
+        };
+        for (line, text) in input_lines {
+            let text = read(line, text)?;
+            if text.trim().is_empty() {
+                finish(section.take())?;
+            }
+            else if let Some((_, name, _, _, ranges)) = section.as_mut() {
+                match text.parse() {
+                    Ok(range) => ranges.push(range),
+                    Err(_) if lenient => (),
+                    Err(e) => return Err(error(name, line, AlmanacErrorKind::InvalidRange(e))),
+                }
+            }
+            else {
+                let name = match text.strip_suffix(" map:") {
+                    Some(name) => name,
+                    None if lenient => continue,
+                    None => return Err(error("maps", line, AlmanacErrorKind::MissingTitle)),
+                };
+                let (source, destination) = name.split_once("-to-")
+                    .ok_or_else(|| error(name, line, AlmanacErrorKind::InvalidTitle(name.to_string())))?;
+                section = Some((line, name.to_string(), source.to_string(), destination.to_string(), Vec::new()));
+            }
+        }
+        finish(section)?;
+
+        // Follow the categories from seed to location, whatever order the maps were listed in.
+        // Maps off that path are dropped, along with any loops among them.
+        let mut path: Vec<(usize, StageTransformer)> = Vec::with_capacity(stages.len());
+        let (mut category, mut line) = ("seed".to_string(), 1);
+        while category != "location" {
+            // Otherwise a loop on the way only shows up as a missing map part way round it.
+            if let Some(start) = path.iter().position(|(_, stage)| stage.source == category) {
+                let mut cycle = path.split_off(start);
+                // Start from the map listed first, and point at it.
+                let first = (0..cycle.len()).min_by_key(|&i| cycle[i].0).unwrap_or(0);
+                cycle.rotate_left(first);
+                let names: Vec<String> = cycle.iter().map(|(_, stage)| stage.source.clone()).collect();
+                let (line, stage) = &cycle[0];
+                return Err(error(&stage.name(), *line, AlmanacErrorKind::CyclicMaps(names)));
+            }
+            let (title_line, stage_transformer) = stages.remove(&category)
+                .ok_or_else(|| error("maps", line, AlmanacErrorKind::MissingStage(category.clone())))?;
+            category = stage_transformer.destination.clone();
+            line = title_line;
+            path.push((title_line, stage_transformer));
+        }
+        let stage_transformers = path.into_iter().map(|(_, stage)| stage).collect();
+        let transformer = AlmanacTransformer { stage_transformers };
+        let composed = transformer.compose();
+        Ok(Almanac { transformer, composed, seeds })
+    }
+
+    pub fn seeds(&self) -> &[Value] { &self.seeds }
+
+    // Part two's reading of the seeds, as (start, length) pairs.
+    pub fn seed_ranges(&self) -> impl Iterator<Item = Interval<Value>> + '_ {
+        self.seeds.chunks_exact(2).map(|c| Interval::with_length(c[0], c[1]))
+    }
+    pub fn composed(&self) -> &StageTransformer { &self.composed }
+
+    pub fn diagnose(&self) -> Vec<StageDiagnosis> {
+        self.transformer.stage_transformers.iter().map(|s| s.diagnose()).collect()
+    }
+
+    pub fn stage(&self, name: &str) -> Option<&StageTransformer> {
+        self.transformer.stage_transformers.iter().find(|s| s.name() == name)
+    }
+
+    // The seed's value once it has been mapped into the given category.
+    pub fn transform_through(&self, seed: Value, category: &str) -> Option<Value> {
+        let mut value = seed;
+        if self.transformer.stage_transformers.first().is_some_and(|s| s.source == category) {
+            return Some(value);
+        }
+        for stage_transformer in &self.transformer.stage_transformers {
+            value = stage_transformer.transform(value);
+            if stage_transformer.destination == category {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+impl Transformer for Almanac {
+    fn transform(&self, input: Value) -> Value {
+        self.composed.transform(input)
+    }
+
+    fn transform_ranges(&self, ranges: Vec<(Value, Value)>) -> Vec<(Value, Value)> {
+        self.composed.transform_ranges(ranges)
+    }
+
+    fn inverse(&self) -> InverseTransformer {
+        self.composed.inverse()
+    }
+}
+
+// The solvers give None without seeds, which parsing never lets through.
+pub fn solve_one(almanac: &Almanac) -> Option<Value> {
+    almanac.seeds.iter().map(|seed| almanac.transform(*seed)).min()
+}
+
+// Seed ranges are cut into blocks of at most this many seeds, so one huge range still spreads
+// over every core. Progress is also counted off a block at a time.
+const BLOCK_SIZE: Value = 1 << 16;
+
+fn split_ranges(ranges: impl Iterator<Item = Interval<Value>>, block_size: Value) -> Vec<Interval<Value>> {
+    ranges
+        .flat_map(|range| range.iter().step_by(block_size as usize).map(move |start| Interval::new(start, start.saturating_add(block_size).min(range.end))))
+        .collect()
+}
+
+fn solve_two_int<T>(ranges: impl Iterator<Item = Interval<Value>>, transformer: &T, progress: &Progress) -> Option<Value>
+    where T: Transformer + Sync
+{
+    // Blocks are large enough that a lock per block costs nothing, and it works for any Value.
+    let best = Mutex::new(Value::MAX);
+    let answer = split_ranges(ranges, BLOCK_SIZE).par_iter().filter_map(|block| {
+        let block_best = block.iter().map(|n| transformer.transform(n)).min();
+        if let (Some(block_best), Ok(mut best)) = (block_best, best.lock()) {
+            *best = block_best.min(*best);
+        }
+        progress.advance(progress_count(block.len()), || format!("best {}", best.lock().map_or(Value::MAX, |b| *b)));
+        block_best
+    }).min();
+    progress.finish(&answer.map_or("no seeds".to_string(), |answer| format!("best {}", answer)));
+    answer
+}
+
+pub fn solve_two(almanac: &Almanac) -> Option<Value> {
+    let seed_ranges: Vec<(Value, Value)> = almanac.seed_ranges().map(|r| (r.start, r.len())).collect();
+    almanac.transform_ranges(seed_ranges).into_iter()
+        .filter(|(_, length)| *length > 0)
+        .map(|(start, _)| start)
+        .min()
+}
+
+// Transforms every seed individually, stage by stage; slow, but useful for cross-checking solve_two.
+pub fn solve_two_brute_force(almanac: &Almanac, progress: &Progress) -> Option<Value> {
+    solve_two_int(almanac.seed_ranges(), &almanac.transformer, progress)
+}
+
+// Saturates, since a u128 almanac can hold more seeds than the progress counter.
+pub fn seed_count(almanac: &Almanac) -> u64 {
+    almanac.seed_ranges().fold(0, |total: u64, r| total.saturating_add(progress_count(r.len())))
+}
+
+// Progress counts in u64 whichever Value is in use.
+#[allow(clippy::useless_conversion)]
+fn progress_count(n: Value) -> u64 {
+    n.try_into().unwrap_or(u64::MAX)
+}
+
+// Walks locations upwards until one maps back into a seed range. Independent of the range
+// splitting in solve_two, so the two can check each other.
+// Without a non-empty seed range there is nothing to find, so it gives up before scanning.
+pub fn solve_two_ascending(almanac: &Almanac) -> Option<Value> {
+    let inverse = almanac.inverse();
+    let seed_ranges: Vec<Interval<Value>> = almanac.seed_ranges().filter(|r| !r.is_empty()).collect();
+    if seed_ranges.is_empty() {
+        return None;
+    }
+    (0..=Value::MAX)
+        .find(|location| inverse.sources(*location).any(|seed| seed_ranges.iter().any(|r| r.contains(seed))))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Solver {
+    BruteForce,
+    Interval,
+    Inverse,
+}
+
+impl Solver {
+    pub const ALL: [Solver; 3] = [Solver::BruteForce, Solver::Interval, Solver::Inverse];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::BruteForce => "brute-force",
+            Solver::Interval => "interval",
+            Solver::Inverse => "inverse",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownSolver(String);
+
+impl fmt::Display for UnknownSolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Solver::ALL.iter().map(|s| s.name()).collect();
+        write!(f, "unknown strategy {:?}; expected one of {}", self.0, names.join(", "))
+    }
+}
+
+impl Error for UnknownSolver {}
+
+impl FromStr for Solver {
+    type Err = UnknownSolver;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Solver::ALL.into_iter().find(|solver| solver.name() == s).ok_or_else(|| UnknownSolver(s.to_string()))
+    }
+}
+
+// The progress line is only drawn by the brute force; the other two finish too quickly to need it.
+pub fn solve_two_with(almanac: &Almanac, solver: Solver, progress: &Progress) -> Option<Value> {
+    match solver {
+        Solver::BruteForce => solve_two_brute_force(almanac, progress),
+        Solver::Interval => solve_two(almanac),
+        Solver::Inverse => solve_two_ascending(almanac),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::strategies;
+    use proptest::prelude::*;
+
+    // The reference: the first range whose source contains the value, else the value itself.
+    fn naive_transform(ranges: &[Range], input: Value) -> Value {
+        ranges.iter()
+            .find(|r| r.source_start <= input && input < r.source_start + r.length)
+            .map_or(input, |r| input - r.source_start + r.destination_start)
+    }
+
+    // Non-overlapping ranges in shuffled order, built from (gap before, length, destination).
+    fn stage_ranges() -> impl Strategy<Value = Vec<Range>> {
+        prop::collection::vec((0..20 as Value, 1..20 as Value, 0..400 as Value), 0..8)
+            .prop_map(|specs| {
+                let mut start = 0;
+                specs.into_iter().map(|(gap, length, destination_start)| {
+                    start += gap;
+                    let range = Range { source_start: start, destination_start, length };
+                    start += length;
+                    range
+                }).collect()
+            })
+            .prop_shuffle()
+    }
+
+    // Ranges anywhere, in listed order, so they can overlap.
+    fn overlapping_stage_ranges() -> impl Strategy<Value = Vec<Range>> {
+        prop::collection::vec((0..100 as Value, 0..40 as Value, 0..400 as Value), 0..8)
+            .prop_map(|specs| specs.into_iter().map(|(source_start, length, destination_start)| Range { source_start, destination_start, length }).collect())
+    }
+
+    // The reference: every seed pushed through each map in turn, read straight off the text.
+    fn naive_solve(input: &str) -> (Value, Value) {
+        let mut sections = input.split("\n\n");
+        let seeds: Vec<Value> = sections.next().unwrap().split_ascii_whitespace().skip(1).map(|s| s.parse().unwrap()).collect();
+        let maps: Vec<Vec<Range>> = sections.map(|s| s.lines().skip(1).map(|l| l.parse().unwrap()).collect()).collect();
+        let location = |seed: Value| maps.iter().fold(seed, |value, ranges| naive_transform(ranges, value));
+        let one = seeds.iter().map(|seed| location(*seed)).min().unwrap();
+        let two = seeds.chunks(2).flat_map(|c| c[0]..c[0] + c[1]).map(location).min().unwrap();
+        (one, two)
+    }
+
+    proptest! {
+        #[test]
+        fn solvers_match_naive(input in strategies::almanac(3)) {
+            let almanac: Almanac = input.parse().unwrap();
+            let (one, two) = naive_solve(&input);
+            prop_assert_eq!(Some(one), solve_one(&almanac));
+            prop_assert_eq!(Some(two), solve_two(&almanac));
+            prop_assert_eq!(Some(two), solve_two_ascending(&almanac));
+        }
+
+        #[test]
+        fn stage_matches_naive(ranges in stage_ranges()) {
+            let st = StageTransformer::new(ranges.clone());
+            for input in 0..450 {
+                prop_assert_eq!(naive_transform(&ranges, input), st.transform(input));
+            }
+        }
+
+        #[test]
+        fn pieces_match_naive(ranges in stage_ranges(), start in 0..200 as Value, length in 0..200 as Value) {
+            let st = StageTransformer::new(ranges.clone());
+            let pieces = st.pieces(Interval::with_length(start, length));
+            prop_assert_eq!(length, pieces.iter().map(|p| p.length).sum::<Value>());
+            for piece in &pieces {
+                for input in piece.source().iter() {
+                    prop_assert_eq!(naive_transform(&ranges, input), piece.map(input));
+                }
+            }
+        }
+
+        #[test]
+        fn overlapping_stage_matches_naive(ranges in overlapping_stage_ranges(), start in 0..200 as Value, length in 0..200 as Value) {
+            let st = StageTransformer::new(ranges.clone());
+            for input in 0..200 {
+                prop_assert_eq!(naive_transform(&ranges, input), st.transform(input));
+            }
+            let pieces = st.pieces(Interval::with_length(start, length));
+            prop_assert_eq!(length, pieces.iter().map(|p| p.length).sum::<Value>());
+            for piece in &pieces {
+                for input in piece.source().iter() {
+                    prop_assert_eq!(naive_transform(&ranges, input), piece.map(input));
+                }
+            }
+        }
+
+        #[test]
+        fn composed_matches_stagewise(stages in prop::collection::vec(stage_ranges(), 1..4)) {
+            let transformer = AlmanacTransformer { stage_transformers: stages.iter().map(|ranges| StageTransformer::new(ranges.clone())).collect() };
+            let composed = transformer.compose();
+            for input in 0..450 {
+                let expected = stages.iter().fold(input, |value, ranges| naive_transform(ranges, value));
+                prop_assert_eq!(expected, composed.transform(input));
+                prop_assert_eq!(expected, transformer.transform(input));
+            }
+        }
+    }
+
+    #[test]
+    fn one_past_the_end_is_unmapped() {
+        let st = StageTransformer::new(vec![Range { source_start: 10, destination_start: 100, length: 5 }]);
+        assert_eq!(104, st.transform(14));
+        assert_eq!(15, st.transform(15));
+    }
+
+    #[test]
+    fn overlapping_ranges() {
+        let st = StageTransformer::new(vec![
+            Range { source_start: 0, destination_start: 1000, length: 100 },
+            Range { source_start: 5, destination_start: 2000, length: 5 },
+        ]);
+        assert_eq!((1050, 1007), (st.transform(50), st.transform(7)));
+        let st = StageTransformer::new(vec![
+            Range { source_start: 5, destination_start: 2000, length: 5 },
+            Range { source_start: 0, destination_start: 1000, length: 100 },
+        ]);
+        assert_eq!((1050, 2002, 1004, 1010), (st.transform(50), st.transform(7), st.transform(4), st.transform(10)));
+        let mut ranges = st.transform_ranges(vec![(3, 10)]);
+        ranges.sort();
+        assert_eq!(vec![(1003, 2), (1010, 3), (2000, 5)], ranges);
+        assert_eq!(2, st.ranges.len());
+    }
+
+    #[test]
+    fn ranges_near_the_top() {
+        let top = format!("{} 0 10", u64::MAX - 5);
+        #[cfg(not(feature = "u128"))]
+        assert_eq!(Err(ParseRangeError::TooLarge), top.parse::<Range>());
+        #[cfg(feature = "u128")]
+        {
+            let st = StageTransformer::new(vec![top.parse().unwrap()]);
+            assert_eq!(u64::MAX as Value + 4, st.transform(9));
+            assert_eq!(vec![(u64::MAX as Value - 3, 2)], st.transform_ranges(vec![(2, 2)]));
+        }
+        let st = StageTransformer::new(vec![format!("{} {} 5", u64::MAX - 5, u64::MAX - 10).parse().unwrap()]);
+        assert_eq!(u64::MAX as Value - 1, st.transform(u64::MAX as Value - 6));
+        assert_eq!(u64::MAX as Value - 5, st.transform(u64::MAX as Value - 5));
+    }
+
+    #[test]
+    fn diagnose() {
+        let st = StageTransformer::named("seed", "soil", vec![
+            Range { source_start: 0, destination_start: 0, length: 10 },
+            Range { source_start: 5, destination_start: 100, length: 10 },
+            Range { source_start: 8, destination_start: 200, length: 4 },
+            Range { source_start: 20, destination_start: 300, length: 5 },
+            Range { source_start: 30, destination_start: 30, length: 0 },
+        ]);
+        let diagnosis = st.diagnose();
+        assert_eq!(vec![Interval::new(5, 10), Interval::new(8, 10), Interval::new(8, 12)], diagnosis.overlaps);
+        assert_eq!(vec![Interval::new(15, 20)], diagnosis.gaps);
+        assert_eq!(vec![Interval::new(0, 10)], diagnosis.identity);
+        assert_eq!("seed-to-soil\n  overlap 5..10\n  overlap 8..10\n  overlap 8..12\n  gap 15..20\n  identity 0..10", diagnosis.to_string());
+        assert_eq!("-to-: ok", StageTransformer::new(vec![Range { source_start: 1, destination_start: 2, length: 3 }]).diagnose().to_string());
+    }
+
+    #[test]
+    fn split_large_ranges() {
+        let ranges = [Interval::with_length(10, 250), Interval::with_length(1000, 3), Interval::with_length(7, 0)];
+        let blocks = split_ranges(ranges.into_iter(), 100);
+        assert_eq!(vec![Interval::new(10, 110), Interval::new(110, 210), Interval::new(210, 260), Interval::new(1000, 1003)], blocks);
+    }
+
+    #[test]
+    fn stage_transformer() {
+        let st = StageTransformer::named("fertilizer", "water", vec![
+                Range {
+                    source_start: 0,
+                    destination_start: 42,
+                    length: 7,
+                },
+                Range {
+                    source_start: 7,
+                    destination_start: 57,
+                    length: 4,
+                },
+                Range {
+                    source_start: 11,
+                    destination_start: 0,
+                    length: 42,
+                },
+                Range {
+                    source_start: 53,
+                    destination_start: 49,
+                    length: 8,
+                },
+        ]);
+        assert_eq!(49, st.transform(53));
+    }
+
+    #[test]
+    fn stage_transform_ranges() {
+        let st = StageTransformer::new(vec![
+            Range { source_start: 10, destination_start: 100, length: 5 },
+            Range { source_start: 20, destination_start: 0, length: 5 },
+        ]);
+        let mut ranges = st.transform_ranges(vec![(8, 20), (30, 2)]);
+        ranges.sort();
+        assert_eq!(vec![(0, 5), (8, 2), (15, 5), (25, 3), (30, 2), (100, 5)], ranges);
+        assert!(st.transform_ranges(vec![(12, 0)]).is_empty());
+        let inverse = st.inverse();
+        assert_eq!(vec![102, 12], inverse.sources(102).collect::<Vec<_>>());
+        assert_eq!(vec![2, 22], inverse.sources(2).collect::<Vec<_>>());
+        assert_eq!(Vec::<Value>::new(), inverse.sources(12).collect::<Vec<_>>());
+        assert_eq!(vec![30], inverse.sources(30).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |input: &str| input.parse::<Almanac>().unwrap_err();
+        assert_eq!(ParseAlmanacError { section: "seeds".to_string(), line: 1, kind: AlmanacErrorKind::MissingSeeds }, err(""));
+        assert_eq!(ParseAlmanacError { section: "seeds".to_string(), line: 1, kind: AlmanacErrorKind::InvalidSeed("x".to_string()) }, err("seeds: 1 x"));
+        assert_eq!(ParseAlmanacError { section: "seeds".to_string(), line: 1, kind: AlmanacErrorKind::NoSeeds }, err("seeds:\n\nseed-to-location map:\n1 2 3"));
+        assert_eq!(AlmanacErrorKind::OddSeedCount(3), err("seeds: 1 2 3\n\nseed-to-location map:\n1 2 3").kind);
+        assert_eq!(AlmanacErrorKind::EmptySeedRanges, err("seeds: 5 0\n\nseed-to-location map:\n1 2 3").kind);
+        assert_eq!("line 1 (seeds): 3 seed numbers, which don't pair up into starts and lengths", err("seeds: 1 2 3").to_string());
+        assert_eq!(ParseAlmanacError { section: "maps".to_string(), line: 3, kind: AlmanacErrorKind::MissingTitle }, err("seeds: 1 1\n\n1 2 3"));
+        let bad_range = err("seeds: 1 1\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-water map:\n1 2");
+        assert_eq!(ParseAlmanacError { section: "soil-to-water".to_string(), line: 7, kind: AlmanacErrorKind::InvalidRange(ParseRangeError::FieldCount(2)) }, bad_range);
+        assert_eq!("line 7 (soil-to-water): invalid range: expected 3 numbers, found 2", bad_range.to_string());
+        assert_eq!(AlmanacErrorKind::InvalidTitle("seed-soil".to_string()), err("seeds: 1 1\n\nseed-soil map:\n1 2 3").kind);
+        assert_eq!(Err(ParseRangeError::InvalidInteger("-1".to_string())), "1 -1 2".parse::<Range>());
+        let duplicate = err("seeds: 1 1\n\nseed-to-soil map:\n1 2 3\n\nseed-to-water map:\n1 2 3");
+        assert_eq!(ParseAlmanacError { section: "seed-to-water".to_string(), line: 6, kind: AlmanacErrorKind::DuplicateSource("seed".to_string()) }, duplicate);
+        let dead_end = err("seeds: 1 1\n\nseed-to-soil map:\n1 2 3\n\nwater-to-location map:\n1 2 3");
+        assert_eq!(ParseAlmanacError { section: "maps".to_string(), line: 3, kind: AlmanacErrorKind::MissingStage("soil".to_string()) }, dead_end);
+        let looped = err("seeds: 1 1\n\nwater-to-seed map:\n1 2 3\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-water map:\n");
+        assert_eq!(ParseAlmanacError { section: "water-to-seed".to_string(), line: 3, kind: AlmanacErrorKind::CyclicMaps(vec!["water".to_string(), "seed".to_string(), "soil".to_string()]) }, looped);
+        let downstream = err("seeds: 1 1\n\nseed-to-soil map:\n1 2 3\n\nwater-to-soil map:\n1 2 3\n\nsoil-to-water map:\n1 2 3");
+        assert_eq!(ParseAlmanacError { section: "water-to-soil".to_string(), line: 6, kind: AlmanacErrorKind::CyclicMaps(vec!["water".to_string(), "soil".to_string()]) }, downstream);
+        let off_path = "seeds: 1 1\n\nseed-to-location map:\n5 1 1\n\nwater-to-light map:\n1 2 3\n\nlight-to-water map:\n1 2 3";
+        assert_eq!(Some(5), solve_one(&off_path.parse::<Almanac>().unwrap()));
+        assert_eq!("line 3 (water-to-seed): the maps go round in a loop: water -> seed -> soil -> water", looped.to_string());
+        let unreadable = Almanac::from_reader(&b"seeds: 1 1\n\nseed-to-soil map:\n\xff"[..]).unwrap_err();
+        assert_eq!(("input", 4), (unreadable.section.as_str(), unreadable.line));
+        assert!(matches!(unreadable.kind, AlmanacErrorKind::Read(_)));
+    }
+
+    #[test]
+    fn lenient() {
+        let pasted = "\nseeds: 1 5  \n\nseed-to-location map:  \n10 0 3\n(the next line is wrong)\n1 2\n";
+        let strict = Almanac::from_reader_with(pasted.as_bytes(), ParseMode::Strict).unwrap_err();
+        assert_eq!(("seeds", 1), (strict.section.as_str(), strict.line));
+        let almanac = Almanac::from_reader_with(pasted.as_bytes(), ParseMode::Lenient).unwrap();
+        assert_eq!(&[1, 5], almanac.seeds());
+        assert_eq!(1, almanac.stage("seed-to-location").unwrap().ranges.len());
+        assert_eq!(Some(5), solve_one(&almanac));
+        let missing = Almanac::from_reader_with("\n\nseeds 1".as_bytes(), ParseMode::Lenient).unwrap_err();
+        assert_eq!(3, missing.line);
+    }
+
+    #[test]
+    fn shuffled_and_extended_maps() {
+        let input = "seeds: 5 20
+
+moon-to-location map:
+0 100 5
+
+seed-to-soil map:
+10 0 10
+
+cheese-to-moon map:
+0 0 100
+
+soil-to-moon map:
+100 10 5";
+        let alm: Almanac = input.parse().unwrap();
+        assert_eq!(vec!["seed-to-soil", "soil-to-moon", "moon-to-location"], alm.transformer.stage_transformers.iter().map(|s| s.name()).collect::<Vec<_>>());
+        assert_eq!(Some(12), alm.transform_through(2, "soil"));
+        assert_eq!(Some(102), alm.transform_through(2, "moon"));
+        assert_eq!(2, alm.transform(2));
+        assert_eq!(15, alm.transform(5));
+        assert_eq!(Some(0), solve_two(&alm));
+    }
+
+    #[test]
+    fn almanac() {
+        let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+        let alm = Almanac::from_str(input).expect("Yeah");
+        let streamed = Almanac::from_reader(std::io::BufReader::with_capacity(16, input.as_bytes())).unwrap();
+        assert_eq!(alm.composed.ranges, streamed.composed.ranges);
+        assert_eq!(82, alm.transform(79));
+        assert_eq!(43, alm.transform(14));
+        assert_eq!(86, alm.transform(55));
+        assert_eq!(35, alm.transform(13));
+        assert_eq!(Some(35), solve_one(&alm));
+        assert_eq!(Some(46), solve_two(&alm));
+        let progress = Progress::new("seeds", seed_count(&alm)).hidden();
+        assert_eq!(Some(46), solve_two_brute_force(&alm, &progress));
+        assert_eq!(27, progress.done());
+        assert_eq!(Some(46), solve_two_ascending(&alm));
+        for solver in Solver::ALL {
+            assert_eq!(Ok(solver), solver.name().parse());
+            assert_eq!(Some(46), solve_two_with(&alm, solver, &Progress::new("seeds", 27).hidden()));
+        }
+        assert_eq!("unknown strategy \"fast\"; expected one of brute-force, interval, inverse", "fast".parse::<Solver>().unwrap_err().to_string());
+        assert_eq!(Some(79), alm.transform_through(79, "seed"));
+        assert_eq!(Some(81), alm.transform_through(79, "soil"));
+        assert_eq!(Some(74), alm.transform_through(79, "light"));
+        assert_eq!(Some(82), alm.transform_through(79, "location"));
+        assert_eq!(None, alm.transform_through(79, "moon"));
+        assert_eq!(Some(2), alm.stage("water-to-light").map(|s| s.ranges.len()));
+        assert!(alm.stage("seed-to-location").is_none());
+        assert_eq!("seed-to-location", alm.composed.name());
+        assert!(alm.diagnose().iter().all(|d| d.to_string().ends_with(": ok")));
+        for seed in 0..120 {
+            assert_eq!(alm.transformer.transform(seed), alm.composed.transform(seed));
+        }
+        assert_eq!(vec![82, 83, 84, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 60], (79..93).map(|s| alm.composed.transform(s)).collect::<Vec<_>>());
+        let mut stagewise = alm.transformer.transform_ranges(vec![(79, 14), (55, 13)]);
+        let mut composed = alm.composed.transform_ranges(vec![(79, 14), (55, 13)]);
+        stagewise.sort();
+        composed.sort();
+        assert_eq!(stagewise.iter().map(|(_, l)| l).sum::<Value>(), 27);
+        assert_eq!(stagewise.iter().flat_map(|(s, l)| *s..s + l).min(), composed.iter().flat_map(|(s, l)| *s..s + l).min());
+    }
+
+}
@@ -0,0 +1,25 @@
+use std::fs::read_to_string;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use four::{parse_cards, Card};
+
+const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+fn matching(c: &mut Criterion) {
+    // Times the real input when it is there, otherwise the example.
+    let input = read_to_string("input.txt").unwrap_or_else(|_| EXAMPLE.to_string());
+    let cards = parse_cards(&input).expect("The input parses");
+    let mut group = c.benchmark_group("matches_count");
+    group.bench_function("bitmask", |b| b.iter(|| cards.iter().map(Card::matches_count).sum::<usize>()));
+    group.bench_function("hash set", |b| b.iter(|| cards.iter().map(Card::matches_count_by_set).sum::<usize>()));
+    group.finish();
+}
+
+criterion_group!(benches, matching);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use four::{parse_cards, solve_one, solve_parallel, solve_two};
+
+// Cards shaped like the real input, from a fixed linear congruential sequence so every run times
+// the same text. Only five numbers are chosen so that copies stay well within u64, and the last
+// ten cards never match so nothing is copied past the end.
+fn synthetic_input(cards: usize) -> String {
+    let mut state: u64 = 2023;
+    let mut next = |bound: u64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) % bound + 1
+    };
+    let mut input = String::new();
+    for id in 1..=cards {
+        let winning: Vec<u64> = (0..10).map(|_| next(99)).collect();
+        let chosen: Vec<u64> = (0..5).map(|_| next(99))
+            .map(|n| if id + 10 > cards && winning.contains(&n) { 100 } else { n })
+            .collect();
+        let format = |nums: &[u64]| nums.iter().map(|n| format!("{:2}", n)).collect::<Vec<_>>().join(" ");
+        input.push_str(&format!("Card {}: {} | {}\n", id, format(&winning), format(&chosen)));
+    }
+    input
+}
+
+fn parallel(c: &mut Criterion) {
+    let input = synthetic_input(1_000_000);
+    let mut group = c.benchmark_group("1M cards");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| b.iter(|| {
+        let cards = parse_cards(&input).expect("The input parses");
+        (solve_one(&cards).expect("Points fit in a u64"), solve_two(&cards).expect("Copies stay in the table"))
+    }));
+    group.bench_function("parallel", |b| b.iter(|| solve_parallel(&input).expect("The input solves")));
+    group.finish();
+}
+
+criterion_group!(benches, parallel);
+criterion_main!(benches);
@@ -0,0 +1,472 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+
+use common::diagnostic::{Diagnostic, ToDiagnostic};
+use common::aoc_parse;
+use common::error::AocError;
+use common::parse::{FormatError, ParseMode};
+use rayon::prelude::*;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub struct Card {
+    pub id: u64,
+    winning_nums: WinningNumbers,
+    chosen_nums: Vec<u64>,
+}
+
+#[derive(Debug)]
+struct WinningNumbers {
+    set: HashSet<u64>,
+    // Bit n is set when n is a winning number; None if any winning number is 128 or more.
+    mask: Option<u128>,
+}
+
+impl FromIterator<u64> for WinningNumbers {
+    fn from_iter<I: IntoIterator<Item = u64>>(numbers: I) -> Self {
+        let set: HashSet<u64> = numbers.into_iter().collect();
+        let mask = set.iter().try_fold(0u128, |mask, n| Some(mask | 1u128.checked_shl((*n).try_into().ok()?)?));
+        Self { set, mask }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CardParseError {
+    MissingColon,
+    MissingPipe,
+    InvalidId(String),
+    InvalidNumber(String),
+}
+
+impl fmt::Display for CardParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardParseError::MissingColon => write!(f, "expected \"Card N: \" before the numbers"),
+            CardParseError::MissingPipe => write!(f, "expected \" | \" between the winning and chosen numbers"),
+            CardParseError::InvalidId(s) => write!(f, "{:?} is not a valid card ID", s),
+            CardParseError::InvalidNumber(s) => write!(f, "{:?} is not a valid number", s),
+        }
+    }
+}
+
+aoc_parse!(Card: CardParseError = "Card " {id} ": " {winning_nums: " "} " | " {chosen_nums: " "});
+
+// Whatever comes before the colon of a line that doesn't start "Card " is taken as the ID.
+impl From<FormatError> for CardParseError {
+    fn from(e: FormatError) -> Self {
+        match e {
+            FormatError::Expected { literal: "Card ", found, .. } => match found.split_once(": ") {
+                Some((id, _)) => CardParseError::InvalidId(id.to_string()),
+                None => CardParseError::MissingColon,
+            },
+            FormatError::Expected { literal: ": ", .. } => CardParseError::MissingColon,
+            FormatError::Expected { .. } | FormatError::TrailingText { .. } => CardParseError::MissingPipe,
+            FormatError::InvalidField { field: "id", text, .. } => CardParseError::InvalidId(text),
+            FormatError::InvalidField { text, .. } => CardParseError::InvalidNumber(text),
+        }
+    }
+}
+
+// Lines are numbered from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseCardsError {
+    pub line: usize,
+    pub error: CardParseError,
+}
+
+impl fmt::Display for ParseCardsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl Error for ParseCardsError {}
+
+impl From<ParseCardsError> for AocError {
+    fn from(e: ParseCardsError) -> Self {
+        AocError::Parse(Diagnostic::at_line(e.line, &e.error.to_string()))
+    }
+}
+
+impl ToDiagnostic for ParseCardsError {
+    fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        let message = self.error.to_string();
+        match &self.error {
+            CardParseError::MissingColon => Diagnostic::whole_line(source, self.line, &message, "no \"Card N: \""),
+            CardParseError::MissingPipe => Diagnostic::whole_line(source, self.line, &message, "no \" | \""),
+            CardParseError::InvalidId(s) => Diagnostic::at_token(source, self.line, s, &message, "not a card ID"),
+            CardParseError::InvalidNumber(s) => Diagnostic::at_token(source, self.line, s, &message, "not a number"),
+        }
+    }
+}
+
+impl Card {
+    // None if the points don't fit in a u64, from 65 matches up.
+    pub fn value(&self) -> Option<u64> {
+        points(self.matches_count())
+    }
+
+    pub fn matches_count(&self) -> usize {
+        match self.winning_nums.mask {
+            Some(mask) => self.chosen_nums.iter().filter(|n| **n < 128 && mask & (1 << **n) != 0).count(),
+            None => self.matches_count_by_set(),
+        }
+    }
+
+    // The chosen numbers that are also winning numbers.
+    pub fn matches(&self) -> impl Iterator<Item = u64> + '_ {
+        self.chosen_nums.iter().copied().filter(|n| self.winning_nums.set.contains(n))
+    }
+
+    pub fn matched_numbers(&self) -> Vec<u64> {
+        self.matches().collect()
+    }
+
+    pub fn matches_count_by_set(&self) -> usize {
+        self.chosen_nums.iter().filter(|n| self.winning_nums.set.contains(*n)).count()
+    }
+}
+
+fn points(matches: usize) -> Option<u64> {
+    match matches {
+        0 => Some(0),
+        n => 2u64.checked_pow((n-1).try_into().ok()?)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScoreError {
+    Overflow { position: usize },
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoreError::Overflow { position } => write!(f, "points won by card {} in the table overflow u64", position + 1),
+        }
+    }
+}
+
+impl Error for ScoreError {}
+
+impl From<ScoreError> for AocError {
+    fn from(e: ScoreError) -> Self {
+        AocError::Solver(e.to_string())
+    }
+}
+
+fn total_points(matches: impl Iterator<Item = usize>) -> Result<u64, ScoreError> {
+    matches.enumerate().try_fold(0u64, |total, (position, n)| {
+        points(n).and_then(|p| total.checked_add(p)).ok_or(ScoreError::Overflow { position })
+    })
+}
+
+pub fn solve_one(cards: &[Card]) -> Result<u64, ScoreError> {
+    total_points(cards.iter().map(Card::matches_count))
+}
+
+// How a card's matches turn into copies of other cards. Every card starts with one copy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyRule {
+    // The puzzle's rule: N matches win one copy of each of the next N cards per copy held.
+    NextCards,
+    // As NextCards, but of the previous N cards, which have already been scored and so never
+    // win anything with their new copies.
+    PreviousCards,
+    // N matches multiply the copies of each of the next N cards by one more than the copies held.
+    Multiply,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CascadeError {
+    PastEnd { position: usize, matches: usize },
+    BeforeStart { position: usize, matches: usize },
+    Overflow { position: usize },
+}
+
+impl fmt::Display for CascadeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CascadeError::PastEnd { position, matches } => write!(f, "card {} in the table has {} matches, which copies cards past the end", position + 1, matches),
+            CascadeError::BeforeStart { position, matches } => write!(f, "card {} in the table has {} matches, which copies cards before the start", position + 1, matches),
+            CascadeError::Overflow { position } => write!(f, "copies won by card {} in the table overflow u64", position + 1),
+        }
+    }
+}
+
+impl Error for CascadeError {}
+
+impl From<CascadeError> for AocError {
+    fn from(e: CascadeError) -> Self {
+        AocError::Solver(e.to_string())
+    }
+}
+
+impl CopyRule {
+    // How many copies of each card are held at the end, given each card's match count. Cards are
+    // copied by their position in the table, so IDs needn't start at 1 or be contiguous.
+    pub fn cascade(&self, matches: &[usize]) -> Result<Vec<u64>, CascadeError> {
+        let mut counts = vec![1u64; matches.len()];
+        for (position, &n) in matches.iter().enumerate() {
+            let m = counts[position];
+            let targets = match self {
+                CopyRule::NextCards | CopyRule::Multiply => {
+                    if n >= matches.len() - position {
+                        return Err(CascadeError::PastEnd { position, matches: n });
+                    }
+                    &mut counts[position + 1..=position + n]
+                },
+                CopyRule::PreviousCards => {
+                    if n > position {
+                        return Err(CascadeError::BeforeStart { position, matches: n });
+                    }
+                    &mut counts[position - n..position]
+                },
+            };
+            for count in targets {
+                let copied = match self {
+                    CopyRule::Multiply => m.checked_add(1).and_then(|factor| count.checked_mul(factor)),
+                    _ => count.checked_add(m),
+                };
+                *count = copied.ok_or(CascadeError::Overflow { position })?;
+            }
+        }
+        Ok(counts)
+    }
+}
+
+pub fn solve_two_with(cards: &[Card], rule: CopyRule) -> Result<u64, CascadeError> {
+    let matches: Vec<usize> = cards.iter().map(Card::matches_count).collect();
+    total_copies(&rule.cascade(&matches)?)
+}
+
+// Reports the card whose copies tipped the total over, so an empty table is just 0.
+fn total_copies(counts: &[u64]) -> Result<u64, CascadeError> {
+    counts.iter().enumerate().try_fold(0u64, |total, (position, n)| total.checked_add(*n).ok_or(CascadeError::Overflow { position }))
+}
+
+pub fn solve_two(cards: &[Card]) -> Result<u64, CascadeError> {
+    solve_two_with(cards, CopyRule::NextCards)
+}
+
+// One card's part of the cascade, for comparing against other implementations.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CardCopies {
+    pub id: u64,
+    pub matches: usize,
+    // None if they overflow u64.
+    pub points: Option<u64>,
+    pub copies: u64,
+}
+
+pub fn copy_report(cards: &[Card], rule: CopyRule) -> Result<Vec<CardCopies>, CascadeError> {
+    let matches: Vec<usize> = cards.iter().map(Card::matches_count).collect();
+    let counts = rule.cascade(&matches)?;
+    Ok(cards.iter().zip(matches).zip(counts)
+        .map(|((card, matches), copies)| CardCopies { id: card.id, matches, points: points(matches), copies })
+        .collect())
+}
+
+// Parses one card per line, lazily.
+pub fn cards(input: &str) -> impl Iterator<Item = Result<Card, ParseCardsError>> + '_ {
+    cards_with(input, ParseMode::global())
+}
+
+// In lenient mode, lines that aren't cards are left out rather than reported.
+pub fn cards_with(input: &str, mode: ParseMode) -> impl Iterator<Item = Result<Card, ParseCardsError>> + '_ {
+    mode.lines(input)
+        .map(|(line, l)| l.parse::<Card>().map_err(|error| ParseCardsError { line, error }))
+        .filter(move |c| mode == ParseMode::Strict || c.is_ok())
+}
+
+pub fn parse_cards(input: &str) -> Result<Vec<Card>, ParseCardsError> {
+    cards(input).collect()
+}
+
+// Each card's match count, parsing and scoring the lines on every core. The cards themselves
+// are dropped as soon as they are counted.
+pub fn match_counts_parallel(input: &str) -> Result<Vec<usize>, ParseCardsError> {
+    let mode = ParseMode::global();
+    let lines: Vec<(usize, &str)> = mode.lines(input).collect();
+    lines.par_iter()
+        .map(|(line, l)| l.parse::<Card>().map(|c| c.matches_count()).map_err(|error| ParseCardsError { line: *line, error }))
+        .filter(|c| mode == ParseMode::Strict || c.is_ok())
+        .collect()
+}
+
+// Both answers for a large input: the match counts come from match_counts_parallel, then the
+// copy cascade runs over them in order.
+pub fn solve_parallel(input: &str) -> Result<(u64, u64), Box<dyn Error>> {
+    let matches = match_counts_parallel(input)?;
+    let one = total_points(matches.iter().copied())?;
+    let two = total_copies(&CopyRule::NextCards.cascade(&matches)?)?;
+    Ok((one, two))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::strategies;
+    use proptest::prelude::*;
+
+    const TEST_DATA: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn test() {
+        let cards = parse_cards(TEST_DATA).unwrap();
+        assert_eq!(Ok(13), solve_one(&cards));
+        assert_eq!(30, solve_two(&cards).unwrap());
+    }
+
+    #[test]
+    fn points_overflow() {
+        let card = |id: usize, matches: usize| {
+            let numbers = (1..=matches).map(|m| m.to_string()).collect::<Vec<_>>().join(" ");
+            format!("Card {}: {} | {}", id, numbers, numbers)
+        };
+        let cards = parse_cards(&card(1, 64)).unwrap();
+        assert_eq!((Some(1 << 63), Ok(1 << 63)), (cards[0].value(), solve_one(&cards)));
+        let cards = parse_cards(&card(1, 65)).unwrap();
+        assert_eq!((None, Err(ScoreError::Overflow { position: 0 })), (cards[0].value(), solve_one(&cards)));
+        // Each fits, but not both.
+        let input = format!("{}\n{}", card(1, 64), card(2, 64));
+        assert_eq!(Err(ScoreError::Overflow { position: 1 }), solve_one(&parse_cards(&input).unwrap()));
+        let error = solve_parallel(&input).unwrap_err();
+        assert_eq!(Some(&ScoreError::Overflow { position: 1 }), error.downcast_ref::<ScoreError>());
+    }
+
+    #[test]
+    fn no_cards() {
+        assert_eq!(Ok(0), solve_two(&[]));
+        assert_eq!(Ok(0), solve_two(&parse_cards("").unwrap()));
+        assert_eq!(Err(CascadeError::Overflow { position: 2 }), total_copies(&[u64::MAX - 1, 1, 1]));
+    }
+
+    #[test]
+    fn matches() {
+        let mut cards = cards(TEST_DATA);
+        let card = cards.next().unwrap().unwrap();
+        assert_eq!(vec![83, 86, 17, 48], card.matches().collect::<Vec<_>>());
+        assert_eq!(card.matches_count(), card.matches().count());
+        assert_eq!(vec![83, 86, 17, 48], card.matched_numbers());
+        let rest: Vec<Vec<u64>> = cards.map(|c| c.unwrap().matched_numbers()).collect();
+        assert_eq!(vec![vec![61, 32], vec![21, 1], vec![84], vec![], vec![]], rest);
+    }
+
+    #[test]
+    fn bitmask_matches_set() {
+        for card in parse_cards(TEST_DATA).unwrap() {
+            assert!(card.winning_nums.mask.is_some());
+            assert_eq!(card.matches_count_by_set(), card.matches_count());
+        }
+        let card: Card = "Card 1: 5 127 200 | 200 127 6 5 300".parse().unwrap();
+        assert_eq!(None, card.winning_nums.mask);
+        assert_eq!(3, card.matches_count());
+        let card: Card = "Card 1: 5 127 | 200 127 6 5 300".parse().unwrap();
+        assert_eq!(2, card.matches_count());
+    }
+
+    #[test]
+    fn positional_copies() {
+        let renumbered = TEST_DATA.lines().enumerate().map(|(i, l)| l.replacen(&format!("Card {}", i + 1), &format!("Card {}", 10 * i + 7), 1));
+        let cards = parse_cards(&renumbered.collect::<Vec<_>>().join("\n")).unwrap();
+        assert_eq!(30, solve_two(&cards).unwrap());
+        let cards = parse_cards("Card 1: 1 2 | 1 3\nCard 2: 4 5 | 4 5").unwrap();
+        assert_eq!(Err(CascadeError::PastEnd { position: 1, matches: 2 }), solve_two(&cards));
+    }
+
+    #[test]
+    fn lenient() {
+        let pasted = format!("\n{}\n\n# pasted from the puzzle page   \n", TEST_DATA.replace('\n', "  \n"));
+        assert_eq!(Some(1), cards_with(&pasted, ParseMode::Strict).find_map(|c| c.err()).map(|e| e.line));
+        let cards: Vec<Card> = cards_with(&pasted, ParseMode::Lenient).collect::<Result<_, _>>().unwrap();
+        assert_eq!(30, solve_two(&cards).unwrap());
+    }
+
+    #[test]
+    fn parallel() {
+        assert_eq!(vec![4, 2, 2, 1, 0, 0], match_counts_parallel(TEST_DATA).unwrap());
+        assert_eq!((13, 30), solve_parallel(TEST_DATA).unwrap());
+        // Card i ends up with 2^i copies for i < 64, then the last card's one copy tips the total.
+        let input = (0..65usize).map(|i| {
+            let numbers = (1..64 - i.min(63)).map(|m| m.to_string()).collect::<Vec<_>>().join(" ");
+            format!("Card {}: {} | {}", i + 1, numbers, numbers)
+        }).collect::<Vec<_>>().join("\n");
+        let error = solve_parallel(&input).unwrap_err();
+        assert_eq!(Some(&CascadeError::Overflow { position: 64 }), error.downcast_ref::<CascadeError>());
+        assert_eq!(Err(CascadeError::Overflow { position: 64 }), solve_two(&parse_cards(&input).unwrap()));
+        let error = match_counts_parallel("Card 1: 1 | 2\nCard 2: 1 | -3").unwrap_err();
+        assert_eq!(ParseCardsError { line: 2, error: CardParseError::InvalidNumber("-3".to_string()) }, error);
+    }
+
+    #[test]
+    fn report() {
+        let cards = parse_cards(TEST_DATA).unwrap();
+        let report = copy_report(&cards, CopyRule::NextCards).unwrap();
+        assert_eq!(vec![1, 2, 4, 8, 14, 1], report.iter().map(|c| c.copies).collect::<Vec<_>>());
+        assert_eq!(CardCopies { id: 3, matches: 2, points: Some(2), copies: 4 }, report[2]);
+        assert_eq!(r#"{"id":1,"matches":4,"points":8,"copies":1}"#, serde_json::to_string(&report[0]).unwrap());
+    }
+
+    #[test]
+    fn copy_rules() {
+        let matches = [0, 2, 1, 0, 1, 0];
+        assert_eq!(Ok(vec![1, 1, 2, 4, 1, 2]), CopyRule::NextCards.cascade(&matches));
+        assert_eq!(Ok(vec![1, 1, 2, 6, 1, 2]), CopyRule::Multiply.cascade(&matches));
+        assert_eq!(Err(CascadeError::BeforeStart { position: 1, matches: 2 }), CopyRule::PreviousCards.cascade(&matches));
+        assert_eq!(Ok(vec![3, 2, 1, 2, 1, 1]), CopyRule::PreviousCards.cascade(&[0, 1, 2, 0, 1, 0]));
+        assert_eq!(Err(CascadeError::PastEnd { position: 4, matches: 3 }), CopyRule::NextCards.cascade(&[0, 2, 1, 0, 3, 0]));
+        assert_eq!(Err(CascadeError::Overflow { position: 7 }), CopyRule::Multiply.cascade(&[3, 3, 3, 3, 3, 3, 3, 3, 0, 0, 0]));
+        let cards = parse_cards(TEST_DATA).unwrap();
+        assert_eq!(Ok(30), solve_two_with(&cards, CopyRule::NextCards));
+    }
+
+    // The reference: matches counted straight off the text, and every copy won dealt out singly.
+    fn naive_solve(input: &str) -> (u64, u64) {
+        let matches: Vec<usize> = input.lines().map(|line| {
+            let (winning, chosen) = line.split_once(':').unwrap().1.split_once('|').unwrap();
+            let winning: Vec<&str> = winning.split_ascii_whitespace().collect();
+            chosen.split_ascii_whitespace().filter(|n| winning.contains(n)).count()
+        }).collect();
+        let points = matches.iter().map(|&m| if m == 0 { 0 } else { 1 << (m - 1) }).sum();
+        let mut pending: Vec<usize> = (0..matches.len()).collect();
+        let mut total = 0;
+        while let Some(i) = pending.pop() {
+            total += 1;
+            pending.extend(i + 1..=i + matches[i]);
+        }
+        (points, total)
+    }
+
+    proptest! {
+        #[test]
+        fn solvers_match_naive(input in strategies::card_set(12)) {
+            let cards = parse_cards(&input).unwrap();
+            let (one, two) = naive_solve(&input);
+            prop_assert_eq!(Ok(one), solve_one(&cards));
+            prop_assert_eq!(Ok(two), solve_two(&cards));
+            prop_assert_eq!((one, two), solve_parallel(&input).unwrap());
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |s: &str| s.parse::<Card>().unwrap_err();
+        assert_eq!(CardParseError::MissingColon, error("Card 1 41 48 | 83 86"));
+        assert_eq!(CardParseError::MissingPipe, error("Card 1: 41 48 83 86"));
+        assert_eq!(CardParseError::InvalidId("x".to_string()), error("Card x: 41 | 83"));
+        assert_eq!(CardParseError::InvalidId("Game 1".to_string()), error("Game 1: 41 | 83"));
+        assert_eq!(CardParseError::InvalidNumber("4l".to_string()), error("Card 1: 4l | 83"));
+        let input = "Card 1: 1 | 2\nCard 2: 1 | -3";
+        let error = parse_cards(input).unwrap_err();
+        assert_eq!(ParseCardsError { line: 2, error: CardParseError::InvalidNumber("-3".to_string()) }, error);
+        assert_eq!((2, 12..14), { let d = error.to_diagnostic(input); (d.line, d.columns) });
+        assert_eq!("error: \"-3\" is not a valid number\n --> line 2", AocError::from(error).to_string());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use five::Almanac;
+
+fuzz_target!(|data: &[u8]| {
+    // The streaming parser sees the raw bytes, invalid UTF-8 and all.
+    let _ = Almanac::from_reader(data);
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = text.parse::<Almanac>();
+    }
+});
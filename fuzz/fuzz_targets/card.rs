@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use four::{parse_cards, Card};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = text.parse::<Card>();
+        if let Ok(cards) = parse_cards(text) {
+            // The bit mask is only a shortcut, so it must always agree with the set.
+            for card in &cards {
+                assert_eq!(card.matches_count_by_set(), card.matches_count());
+            }
+        }
+    }
+});
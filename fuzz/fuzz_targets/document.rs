@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use six::{Document, WellKernedDocument};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = text.parse::<Document>();
+        let _ = text.parse::<WellKernedDocument>();
+    }
+});
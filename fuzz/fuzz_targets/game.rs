@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use two::{parse_games, Drawing, Game};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = text.parse::<Drawing>();
+        let _ = text.parse::<Game>();
+        let _ = parse_games(text);
+    }
+});
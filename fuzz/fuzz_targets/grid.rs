@@ -0,0 +1,14 @@
+#![no_main]
+
+use common::grid::{AoCGrid, RaggedLines};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = AoCGrid::try_new(text);
+        let _ = AoCGrid::try_with_ragged_lines(text, RaggedLines::TrimTrailingWhitespace);
+        if let Ok(schematic) = three::parse(text) {
+            let _ = schematic.grid_numbers().count();
+        }
+    }
+});
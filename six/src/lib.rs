@@ -0,0 +1,449 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use common::diagnostic::{Diagnostic, ToDiagnostic};
+use common::error::AocError;
+use common::parse::{concat_digits, ParseMode, Table, TableError};
+
+#[derive(Debug)]
+pub struct Document {
+    pub times: Vec<u64>,
+    pub distances: Vec<u64>,
+}
+
+#[derive(Debug)]
+pub struct WellKernedDocument {
+    pub time: u64,
+    pub distance: u64,
+}
+
+// Lines are numbered from 1.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ParseDocumentError {
+    MissingLine { line: usize, label: &'static str },
+    MissingLabel { line: usize, label: &'static str },
+    Unlabelled { line: usize },
+    InvalidNumber { line: usize, token: String },
+    Ragged { line: usize, expected: usize, found: usize },
+}
+
+impl From<TableError> for ParseDocumentError {
+    fn from(e: TableError) -> Self {
+        match e {
+            TableError::MissingLabel { line } => ParseDocumentError::Unlabelled { line },
+            TableError::InvalidNumber { line, token } => ParseDocumentError::InvalidNumber { line, token },
+            TableError::Ragged { line, expected, found } => ParseDocumentError::Ragged { line, expected, found },
+        }
+    }
+}
+
+impl fmt::Display for ParseDocumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDocumentError::MissingLine { line, label } => write!(f, "line {}: expected a {:?} line", line, label),
+            ParseDocumentError::MissingLabel { line, label } => write!(f, "line {}: expected the line to start with {:?}", line, label),
+            ParseDocumentError::Unlabelled { line } => write!(f, "line {}: expected a label ending in ':'", line),
+            ParseDocumentError::InvalidNumber { line, token } => write!(f, "line {}: {:?} is not a valid number", line, token),
+            ParseDocumentError::Ragged { line, expected, found } => write!(f, "line {}: expected {} numbers, as on the first line, found {}", line, expected, found),
+        }
+    }
+}
+
+impl Error for ParseDocumentError {}
+
+impl From<ParseDocumentError> for AocError {
+    fn from(e: ParseDocumentError) -> Self {
+        let line = match e {
+            ParseDocumentError::MissingLine { line, .. } | ParseDocumentError::MissingLabel { line, .. } | ParseDocumentError::Unlabelled { line }
+                | ParseDocumentError::InvalidNumber { line, .. } | ParseDocumentError::Ragged { line, .. } => line,
+        };
+        AocError::Parse(Diagnostic::at_line(line, &e.to_diagnostic("").message))
+    }
+}
+
+impl ToDiagnostic for ParseDocumentError {
+    fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        match self {
+            ParseDocumentError::MissingLine { line, label } => Diagnostic::whole_line(source, *line, &format!("expected a {:?} line", label), "missing"),
+            ParseDocumentError::MissingLabel { line, label } => Diagnostic::whole_line(source, *line, &format!("expected the line to start with {:?}", label), "wrong label"),
+            ParseDocumentError::Unlabelled { line } => Diagnostic::whole_line(source, *line, "expected a label ending in ':'", "no label"),
+            ParseDocumentError::InvalidNumber { line, token } => Diagnostic::at_token(source, *line, token, &format!("{:?} is not a valid number", token), "not a number"),
+            ParseDocumentError::Ragged { line, expected, found } => Diagnostic::whole_line(source, *line, &format!("expected {} numbers, as on the first line, found {}", expected, found), &format!("expected {}", expected)),
+        }
+    }
+}
+
+// The next row's numbers, which must have the given label. In lenient mode, rows that don't are
+// skipped. line is the one expected, for when the table runs out.
+fn labelled_row<'a, I: Iterator<Item = (usize, &'a str, &'a [u64])>>(rows: &mut I, mode: ParseMode, line: usize, label: &'static str) -> Result<&'a [u64], ParseDocumentError> {
+    loop {
+        let (n, found, values) = rows.next().ok_or(ParseDocumentError::MissingLine { line, label })?;
+        if found == label.trim_end_matches(':') {
+            return Ok(values);
+        }
+        if mode == ParseMode::Strict {
+            return Err(ParseDocumentError::MissingLabel { line: n, label });
+        }
+    }
+}
+
+// As labelled_row, but for a line of text rather than a row of numbers: the rest of the line
+// and its number.
+fn labelled<'a, I: Iterator<Item = (usize, &'a str)>>(lines: &mut I, mode: ParseMode, line: usize, label: &'static str) -> Result<(usize, &'a str), ParseDocumentError> {
+    loop {
+        let (n, text) = lines.next().ok_or(ParseDocumentError::MissingLine { line, label })?;
+        match text.trim_start().strip_prefix(label) {
+            Some(rest) => return Ok((n, rest)),
+            None if mode == ParseMode::Lenient => continue,
+            None => return Err(ParseDocumentError::MissingLabel { line: n, label }),
+        }
+    }
+}
+
+// The digit groups on the line read as one number. Only builds a string to report an error.
+fn parse_kerned(line: usize, s: &str) -> Result<u64, ParseDocumentError> {
+    concat_digits(s.split_ascii_whitespace())
+        .map_err(|_| ParseDocumentError::InvalidNumber { line, token: s.split_ascii_whitespace().collect() })
+}
+
+impl FromStr for Document {
+    type Err = ParseDocumentError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::parse_with(s, ParseMode::global())
+    }
+}
+
+impl Document {
+    // Every line must be a row of the table, Time first and Distance second; any rows after
+    // those are checked but unused.
+    pub fn parse_with(s: &str, mode: ParseMode) -> Result<Self, ParseDocumentError> {
+        let table: Table = Table::parse_with(s, mode)?;
+        let mut rows = table.rows();
+        let times = labelled_row(&mut rows, mode, 1, "Time:")?.to_vec();
+        let distances = labelled_row(&mut rows, mode, 2, "Distance:")?.to_vec();
+        Ok(Self { times, distances })
+    }
+
+    pub fn races(&self) -> impl Iterator<Item = Race> + '_ {
+        self.times.iter().zip(self.distances.iter()).map(|(time, distance)| Race::new(*time, *distance))
+    }
+}
+
+impl FromStr for WellKernedDocument {
+    type Err = ParseDocumentError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WellKernedDocument::parse_with(s, ParseMode::global())
+    }
+}
+
+impl WellKernedDocument {
+    // Not a Table, as kerning makes the spaces meaningless: the lines needn't have the same
+    // number of digit groups.
+    pub fn parse_with(s: &str, mode: ParseMode) -> Result<Self, ParseDocumentError> {
+        let mut lines = mode.lines(s);
+        let (time_line, first_line) = labelled(&mut lines, mode, 1, "Time:")?;
+        let (distance_line, second_line) = labelled(&mut lines, mode, 2, "Distance:")?;
+
+        let time = parse_kerned(time_line, first_line)?;
+        let distance = parse_kerned(distance_line, second_line)?;
+        Ok(Self { time, distance })
+    }
+
+    pub fn race(&self) -> Race {
+        Race::new(self.time, self.distance)
+    }
+}
+
+// The first value in lo..hi for which pred holds, or hi if none does. pred must be false then
+// true across the range.
+fn first_where<F: Fn(u64) -> bool>(mut lo: u64, mut hi: u64, pred: F) -> u64 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) { hi = mid; } else { lo = mid + 1; }
+    }
+    lo
+}
+
+// How the boat moves: every millisecond the button is held adds acceleration to a starting
+// speed of initial_speed, in millimetres per millisecond.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Physics {
+    pub acceleration: u64,
+    pub initial_speed: u64,
+}
+
+impl Default for Physics {
+    // The puzzle's boats.
+    fn default() -> Self {
+        Self { acceleration: 1, initial_speed: 0 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Race {
+    pub time: u64,
+    pub record: u64,
+    pub physics: Physics,
+}
+
+impl Race {
+    pub fn new(time: u64, record: u64) -> Self {
+        Self { time, record, physics: Physics::default() }
+    }
+
+    pub fn with_physics(mut self, physics: Physics) -> Self {
+        self.physics = physics;
+        self
+    }
+
+    // Holding the button for the whole race, or longer, goes nowhere. Widened, since the product
+    // of two u64s needs up to 128 bits; saturates beyond that, which can only ever be a win.
+    pub fn distance(&self, hold: u64) -> u128 {
+        let speed = (self.physics.acceleration as u128).saturating_mul(hold as u128).saturating_add(self.physics.initial_speed as u128);
+        speed.saturating_mul(self.time.saturating_sub(hold) as u128)
+    }
+
+    // Matching the record is not enough.
+    pub fn beats(&self, hold: u64) -> bool {
+        self.distance(hold) > self.record as u128
+    }
+
+    // The lowest hold that goes furthest. Distance rises up to here and falls after it.
+    pub fn peak(&self) -> u64 {
+        first_where(0, self.time, |hold| self.distance(hold + 1) <= self.distance(hold))
+    }
+
+    // Where acceleration * h^2 - (acceleration * time - initial_speed) * h + record - initial_speed * time
+    // crosses zero, rounded down. None when there is no quadratic or the numbers outgrow i128.
+    fn root_estimates(&self) -> Option<(i128, i128)> {
+        let (a, v, t, r) = (self.physics.acceleration as i128, self.physics.initial_speed as i128, self.time as i128, self.record as i128);
+        if a == 0 {
+            return None;
+        }
+        let sum = a.checked_mul(t)?.checked_add(v)?;
+        let discriminant = sum.checked_mul(sum)?.checked_sub(a.checked_mul(r)?.checked_mul(4)?)?;
+        if discriminant < 0 {
+            return None;
+        }
+        let b = a.checked_mul(t)? - v;
+        let root = discriminant.isqrt();
+        Some(((b - root).div_euclid(2 * a), (b + root).div_euclid(2 * a)))
+    }
+
+    // Solves for the two points where the distance matches the record, then nudges each onto
+    // the first and last winning hold.
+    pub fn winning_holds(&self) -> RangeInclusive<u64> {
+        let peak = self.peak();
+        if !self.beats(peak) {
+            return RangeInclusive::new(1, 0);
+        }
+        let Some((low, high)) = self.root_estimates() else {
+            return self.winning_holds_binary_search();
+        };
+        let mut low = low.clamp(0, peak as i128) as u64;
+        let mut high = high.clamp(peak as i128, self.time as i128) as u64;
+        // isqrt and the division both round down, so each boundary can be a step either side.
+        while !self.beats(low) {
+            low += 1;
+        }
+        while low > 0 && self.beats(low - 1) {
+            low -= 1;
+        }
+        while !self.beats(high) {
+            high -= 1;
+        }
+        while high < self.time && self.beats(high + 1) {
+            high += 1;
+        }
+        low..=high
+    }
+
+    // Each boundary is a binary search over one side of the peak. Shares nothing with the root
+    // finding, so the two check each other.
+    pub fn winning_holds_binary_search(&self) -> RangeInclusive<u64> {
+        let peak = self.peak();
+        if !self.beats(peak) {
+            return RangeInclusive::new(1, 0);
+        }
+        let first = first_where(0, peak, |hold| self.beats(hold));
+        // Searching for the hold before the first loser keeps clear of time + 1.
+        let last = first_where(peak, self.time, |hold| !self.beats(hold + 1));
+        first..=last
+    }
+
+    // The number of ways to win.
+    pub fn margin(&self) -> u64 {
+        hold_count(&self.winning_holds())
+    }
+
+    // Counts outward from the peak one hold at a time; slow, but obviously right.
+    pub fn margin_by_stepping(&self) -> u64 {
+        let peak = self.peak();
+        let below = (0..=peak).rev().take_while(|hold| self.beats(*hold)).count();
+        let above = (peak + 1..=self.time).take_while(|hold| self.beats(*hold)).count();
+        (below + above) as u64
+    }
+}
+
+pub fn hold_count(holds: &RangeInclusive<u64>) -> u64 {
+    if holds.is_empty() { 0 } else { holds.end() - holds.start() + 1 }
+}
+
+// None if the product of the margins overflows.
+pub fn solve_one(doc: &Document) -> Option<u64> {
+    doc.races().try_fold(1u64, |product, race| product.checked_mul(race.margin()))
+}
+
+pub fn solve_two(wkd: &WellKernedDocument) -> u64 {
+    wkd.race().margin()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn solvers_agree_on_small_races(time in 0u64..5_000, record in 0u64..7_000_000) {
+            let race = Race::new(time, record);
+            prop_assert_eq!(race.margin_by_stepping(), race.margin());
+            prop_assert_eq!(race.winning_holds(), race.winning_holds_binary_search());
+        }
+
+        // A record that some hold exactly matches; that hold must not count as a win.
+        #[test]
+        fn ties_do_not_win(time in 1u64..5_000, hold in 0u64..5_000) {
+            let hold = hold % time;
+            let race = Race::new(time, race_distance(time, hold));
+            prop_assert!(!race.winning_holds().contains(&hold));
+            prop_assert_eq!(race.margin_by_stepping(), race.margin());
+            prop_assert_eq!(race.winning_holds(), race.winning_holds_binary_search());
+        }
+
+        #[test]
+        fn solvers_agree_on_huge_races(time in any::<u64>(), record in any::<u64>()) {
+            let race = Race::new(time, record);
+            prop_assert_eq!(race.winning_holds(), race.winning_holds_binary_search());
+        }
+    }
+
+    fn race_distance(time: u64, hold: u64) -> u64 {
+        Race::new(time, 0).distance(hold) as u64
+    }
+
+    const INPUT: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn part_one() {
+        let doc: Document = INPUT.parse().expect("Can parse valid document");
+        assert_eq!(vec![7, 15, 30], doc.times);
+        assert_eq!(vec![9, 40, 200], doc.distances);
+        assert_eq!(Some(288), solve_one(&doc));
+        assert_eq!(vec![2..=5, 4..=11, 11..=19], doc.races().map(|r| r.winning_holds()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn part_two() {
+        let doc: WellKernedDocument = INPUT.parse().expect("Can parse valid document");
+        assert_eq!(71530, doc.time);
+        assert_eq!(940200, doc.distance);
+        assert_eq!(71503, solve_two(&doc));
+        assert_eq!(71503, doc.race().margin_by_stepping());
+        assert_eq!(14..=71516, doc.race().winning_holds_binary_search());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Err(ParseDocumentError::MissingLine { line: 1, label: "Time:" }), "".parse::<Document>().map(|_| ()));
+        assert_eq!(Err(ParseDocumentError::MissingLine { line: 2, label: "Distance:" }), "Time: 7".parse::<WellKernedDocument>().map(|_| ()));
+        assert_eq!(Err(ParseDocumentError::MissingLabel { line: 2, label: "Distance:" }), "Time: 7\nDist: 9".parse::<Document>().map(|_| ()));
+        let invalid = "Time: 7 x5\nDistance: 9 40".parse::<Document>().unwrap_err();
+        assert_eq!(ParseDocumentError::InvalidNumber { line: 1, token: "x5".to_string() }, invalid);
+        assert_eq!("line 1: \"x5\" is not a valid number", invalid.to_string());
+        let ragged = "Time: 7 15 30\nDistance: 9 40".parse::<Document>().unwrap_err();
+        assert_eq!(ParseDocumentError::Ragged { line: 2, expected: 3, found: 2 }, ragged);
+        assert_eq!("line 2: expected 3 numbers, as on the first line, found 2", ragged.to_string());
+        assert_eq!(Err(ParseDocumentError::Unlabelled { line: 1 }), "Time 7 15\nDistance: 9 40".parse::<Document>().map(|_| ()));
+        // Kerned lines needn't line up.
+        assert_eq!(Ok((71530, 940)), "Time: 7 15 30\nDistance: 9 40".parse::<WellKernedDocument>().map(|d| (d.time, d.distance)));
+        assert_eq!(Err(ParseDocumentError::InvalidNumber { line: 2, token: String::new() }), "Time: 7\nDistance:".parse::<WellKernedDocument>().map(|_| ()));
+        assert_eq!(Err(ParseDocumentError::InvalidNumber { line: 1, token: "99999999999999999999".to_string() }), "Time: 9999999999 9999999999\nDistance: 1".parse::<WellKernedDocument>().map(|_| ()));
+    }
+
+    #[test]
+    fn lenient() {
+        let pasted = "\n  Time:      7  15   30  \nnotes\n\nDistance:  9  40  200\n";
+        assert_eq!(Err(ParseDocumentError::Unlabelled { line: 1 }), Document::parse_with(pasted, ParseMode::Strict).map(|_| ()));
+        assert_eq!(Err(ParseDocumentError::MissingLabel { line: 1, label: "Time:" }), WellKernedDocument::parse_with(pasted, ParseMode::Strict).map(|_| ()));
+        let doc = Document::parse_with(pasted, ParseMode::Lenient).unwrap();
+        assert_eq!((vec![7, 15, 30], vec![9, 40, 200]), (doc.times, doc.distances));
+        assert_eq!(71530, WellKernedDocument::parse_with(pasted, ParseMode::Lenient).unwrap().time);
+        let invalid = WellKernedDocument::parse_with("Time: 7\n\nDistance: 9x", ParseMode::Lenient).unwrap_err();
+        assert_eq!(ParseDocumentError::InvalidNumber { line: 3, token: "9x".to_string() }, invalid);
+    }
+
+    #[test]
+    fn race() {
+        let race = Race::new(7, 9);
+        assert_eq!(vec![0, 6, 10, 12, 12, 10, 6, 0, 0], (0..9).map(|h| race.distance(h)).collect::<Vec<_>>());
+        let doc = Document { times: vec![u64::MAX, u64::MAX], distances: vec![0, 0] };
+        assert_eq!(None, solve_one(&doc));
+        assert!(!race.beats(1) && race.beats(2));
+        assert_eq!(0, Race::new(0, 0).margin());
+        assert_eq!(0, Race::new(0, 0).margin_by_stepping());
+    }
+
+    #[test]
+    fn physics() {
+        assert_eq!(3, Race::new(7, 0).peak());
+        let coasting = Race::new(7, 9).with_physics(Physics { acceleration: 1, initial_speed: 2 });
+        assert_eq!(vec![14, 18, 20, 20, 18, 14, 8, 0], (0..8).map(|h| coasting.distance(h)).collect::<Vec<_>>());
+        assert_eq!(2, coasting.peak());
+        assert_eq!(0..=5, coasting.winning_holds());
+        let fixed_speed = Race::new(10, 20).with_physics(Physics { acceleration: 0, initial_speed: 3 });
+        assert_eq!(0, fixed_speed.peak());
+        assert_eq!(0..=3, fixed_speed.winning_holds());
+        for acceleration in 0..4 {
+            for initial_speed in 0..5 {
+                let physics = Physics { acceleration, initial_speed };
+                for time in 0..25 {
+                    for record in 0..200 {
+                        let race = Race::new(time, record).with_physics(physics);
+                        let winners: Vec<u64> = (0..=time).filter(|h| race.beats(*h)).collect();
+                        let expected = match (winners.first(), winners.last()) {
+                            (Some(first), Some(last)) => *first..=*last,
+                            _ => RangeInclusive::new(1, 0),
+                        };
+                        assert_eq!(expected, race.winning_holds(), "{:?}", race);
+                        assert_eq!(expected, race.winning_holds_binary_search(), "{:?}", race);
+                        assert_eq!(winners.len() as u64, race.margin_by_stepping(), "{:?}", race);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solvers_agree() {
+        for time in 0..60 {
+            for record in 0..(time * time / 4 + 3) {
+                let race = Race::new(time, record);
+                assert_eq!(race.margin_by_stepping(), race.margin(), "{:?}", race);
+                assert_eq!(race.winning_holds(), race.winning_holds_binary_search(), "{:?}", race);
+            }
+        }
+        for race in [Race::new(4_000_000_000, 3_999_999_999_000_000_000), Race::new(u64::MAX, u64::MAX), Race::new(u64::MAX, 0), Race::new(u64::MAX - 1, u64::MAX)] {
+            assert_eq!(race.winning_holds_binary_search(), race.winning_holds(), "{:?}", race);
+        }
+        assert_eq!(1..=u64::MAX - 1, Race::new(u64::MAX, 0).winning_holds());
+        assert_eq!((u64::MAX as u128 / 2).pow(2) + u64::MAX as u128 / 2, Race::new(u64::MAX, 0).distance(u64::MAX / 2));
+        // The record is exactly reachable by holding for 10, which does not count as a win.
+        assert_eq!(0, Race::new(20, 100).margin());
+        assert_eq!(1, Race::new(20, 99).margin());
+        assert_eq!(3, Race::new(20, 98).margin());
+    }
+}
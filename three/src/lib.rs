@@ -0,0 +1,959 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::str::FromStr;
+use std::vec;
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use common::grid::{AoCGrid, AoCGridAdjacentPoints, CellRunIterator, Grid, Grid2D, GridError, GridIterator, Point};
+use common::diagnostic::Diagnostic;
+use common::error::AocError;
+use common::grid::RaggedLines;
+use common::highlight::{highlight, Colour};
+use common::parse::ParseMode;
+
+pub mod stream;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SchematicError {
+    Grid(GridError),
+    InvalidCoordinate(Point),
+    NumberTooLarge { at: Point },
+    Overflow,
+    Read(String),
+}
+
+impl fmt::Display for SchematicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchematicError::Grid(e) => write!(f, "Invalid schematic: {}", e),
+            SchematicError::InvalidCoordinate(p) => write!(f, "No cell at {}", p),
+            SchematicError::NumberTooLarge { at } => write!(f, "Number starting at {} does not fit in a u64", at),
+            SchematicError::Overflow => write!(f, "Answer does not fit in a u64"),
+            SchematicError::Read(e) => write!(f, "Failed to read schematic: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SchematicError {}
+
+impl From<SchematicError> for AocError {
+    fn from(e: SchematicError) -> Self {
+        match e {
+            SchematicError::Grid(e) => AocError::from(e),
+            SchematicError::NumberTooLarge { at } => AocError::Parse(Diagnostic::at_line(at.y + 1, &e.to_string())),
+            e => AocError::Solver(e.to_string()),
+        }
+    }
+}
+
+impl From<GridError> for SchematicError {
+    fn from(e: GridError) -> Self {
+        SchematicError::Grid(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct GridNumber {
+    pub value: u64,
+    pub start_coord: Point,
+    pub coord_length: usize,
+}
+
+impl GridNumber {
+    fn part_number(&self, engine_schematic: &EngineSchematic) -> Option<u64> {
+        self.part_number_with(engine_schematic, |_| true)
+    }
+
+    // Only symbols the predicate accepts count; digits and '.' never do.
+    pub fn part_number_with<F: Fn(char) -> bool>(&self, engine_schematic: &EngineSchematic, is_symbol: F) -> Option<u64> {
+        GridNumberAdjacentData::new(self, engine_schematic)
+            .filter(|(_, cell_type)| *cell_type == GridDataType::Symbol)
+            .any(|(at, _)| engine_schematic.grid.get(&at).and_then(|cell| cell.chars().next()).is_some_and(&is_symbol))
+            .then_some(self.value)
+    }
+
+    // Each neighbouring symbol once, in reading order.
+    pub fn adjacent_symbols(&self, engine_schematic: &EngineSchematic) -> Vec<(Point, char)> {
+        let mut symbols: Vec<(Point, char)> = GridNumberAdjacentData::new(self, engine_schematic)
+            .filter(|(_, cell_type)| *cell_type == GridDataType::Symbol)
+            .filter_map(|(at, _)| engine_schematic.grid.get(&at).and_then(|cell| cell.chars().next()).map(|c| (at, c)))
+            .collect();
+        symbols.sort_by_key(|(at, _)| (at.y, at.x));
+        symbols.dedup();
+        symbols
+    }
+}
+
+#[derive(Debug)]
+pub struct EngineSchematic<'a> {
+    grid: AoCGrid<'a>,
+    // Classified once up front so scans don't re-parse each one-character cell.
+    cells: Grid<GridDataType>,
+}
+
+impl<'a> Grid2D for EngineSchematic<'a> {
+    fn width(&self) -> usize { self.grid.width() }
+    fn height(&self) -> usize { self.grid.height() }
+    fn valid_coordinate(&self, p: &Point) -> bool {
+        self.grid.valid_coordinate(p)
+    }
+
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GridDataType {
+    Digit(u64),
+    Symbol,
+    Space,
+}
+
+#[derive(Debug)]
+struct ParseGridDataTypeError;
+
+impl GridDataType {
+    fn digit(&self) -> Option<u64> {
+        match self {
+            GridDataType::Digit(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    fn is_digit(&self) -> bool {
+        self.digit().is_some()
+    }
+}
+
+impl FromStr for GridDataType {
+    type Err = ParseGridDataTypeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            [b] => Ok(Self::from(*b)),
+            _ => Err(ParseGridDataTypeError),
+        }
+    }
+}
+
+impl From<u8> for GridDataType {
+    fn from(b: u8) -> Self {
+        match b {
+            b'.' => Self::Space,
+            b'0'..=b'9' => Self::Digit((b - b'0') as u64),
+            _ => Self::Symbol,
+        }
+    }
+}
+
+fn cell_at<'a>(engine_schematic: &'a EngineSchematic, at: Point) -> Result<&'a str, SchematicError> {
+    engine_schematic.grid.get(&at).ok_or(SchematicError::InvalidCoordinate(at))
+}
+
+fn cell_type(engine_schematic: &EngineSchematic, at: Point) -> Result<GridDataType, SchematicError> {
+    engine_schematic.cells.get(&at).copied().ok_or(SchematicError::InvalidCoordinate(at))
+}
+
+pub struct GridNumberIterator<'a> {
+    runs: CellRunIterator<'a, GridDataType, fn(&GridDataType) -> bool>,
+}
+
+impl<'a> GridNumberIterator<'a> {
+    fn new(engine_schematic: &'a EngineSchematic<'a>) -> Self {
+        Self { runs: engine_schematic.cells.runs(GridDataType::is_digit) }
+    }
+
+    fn row(engine_schematic: &'a EngineSchematic<'a>, y: usize) -> Self {
+        Self { runs: engine_schematic.cells.row_runs(y, GridDataType::is_digit) }
+    }
+}
+
+impl<'a> Iterator for GridNumberIterator<'a> {
+    type Item = Result<GridNumber, SchematicError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start_coord, digits) = self.runs.next()?;
+        let value = digits.iter().filter_map(GridDataType::digit).try_fold(0u64, |v, d| v.checked_mul(10)?.checked_add(d));
+        Some(value.map(|value| GridNumber { value, start_coord, coord_length: digits.len() })
+            .ok_or(SchematicError::NumberTooLarge { at: start_coord }))
+    }
+}
+
+impl<'a> FusedIterator for GridNumberIterator<'a> {}
+
+struct GridNumberAdjacentData {
+    adjacent_points: vec::IntoIter<(Point, GridDataType)>,
+}
+
+impl GridNumberAdjacentData {
+    fn new(grid_number: &GridNumber, engine_schematic: &EngineSchematic) -> Self {
+        let mut adjacent_points: Vec<(Point, GridDataType)> = Vec::with_capacity(2*grid_number.coord_length + 6);
+        for i in 0..grid_number.coord_length {
+            let this_point = Point { x: grid_number.start_coord.x + i, y: grid_number.start_coord.y };
+            for adj in AoCGridAdjacentPoints::new(engine_schematic, &this_point) {
+                if let Some(cell_type) = engine_schematic.cells.get(&adj) {
+                    adjacent_points.push((adj, *cell_type));
+                }
+            }
+        }
+        Self { adjacent_points: adjacent_points.into_iter() }
+    }
+}
+
+impl Iterator for GridNumberAdjacentData {
+    type Item = (Point, GridDataType);
+    fn next(&mut self) -> Option<Self::Item> {
+        self.adjacent_points.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.adjacent_points.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for GridNumberAdjacentData {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.adjacent_points.next_back()
+    }
+}
+
+impl ExactSizeIterator for GridNumberAdjacentData {}
+impl FusedIterator for GridNumberAdjacentData {}
+
+pub struct GearIterator {
+    gears: Vec<Gear>,
+    iter_number: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    fn accepts(&self, n: usize) -> bool {
+        match self {
+            Arity::Exactly(a) => n == *a,
+            Arity::AtLeast(a) => n >= *a,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GearSpec {
+    pub symbol: char,
+    pub arity: Arity,
+}
+
+impl Default for GearSpec {
+    fn default() -> Self {
+        Self { symbol: '*', arity: Arity::Exactly(2) }
+    }
+}
+
+impl GearIterator {
+    fn new(engine_schematic: &EngineSchematic, spec: &GearSpec) -> Result<Self, SchematicError> {
+        let mut gears = Vec::new();
+        let lookup = PartNumberIndex::new(engine_schematic)?;
+        for (point, _) in engine_schematic.symbols().filter(|(_, c)| *c == spec.symbol) {
+            gears.extend(gear_at(engine_schematic, &lookup, point, spec));
+        }
+        Ok(Self { gears, iter_number: 0 })
+    }
+}
+
+fn gear_at(engine_schematic: &EngineSchematic, lookup: &PartNumberIndex, point: Point, spec: &GearSpec) -> Option<Gear> {
+    let mut adjacent_part_numbers = HashSet::new();
+    for adj in AoCGridAdjacentPoints::new(engine_schematic, &point) {
+        if let Some(grid_number) = lookup.get(&adj) {
+           adjacent_part_numbers.insert(grid_number);
+        }
+    }
+
+    if spec.arity.accepts(adjacent_part_numbers.len()) {
+        let mut numbers: Vec<GridNumber> = adjacent_part_numbers.into_iter().copied().collect();
+        numbers.sort_by_key(|g| (g.start_coord.y, g.start_coord.x));
+        Some(Gear { point, numbers })
+    }
+    else {
+        None
+    }
+}
+
+impl Iterator for GearIterator {
+    type Item = Gear;
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.gears.get(self.iter_number);
+        self.iter_number += 1;
+        result.cloned()
+    }
+}
+
+pub struct SymbolIterator<'a> {
+    engine_schematic: &'a EngineSchematic<'a>,
+    points: GridIterator,
+}
+
+impl<'a> Iterator for SymbolIterator<'a> {
+    type Item = (Point, char);
+    fn next(&mut self) -> Option<Self::Item> {
+        for point in self.points.by_ref() {
+            if let Some(GridDataType::Symbol) = self.engine_schematic.cells.get(&point) {
+                if let Some(c) = self.engine_schematic.grid.get(&point).and_then(|cell| cell.chars().next()) {
+                    return Some((point, c));
+                }
+            }
+        }
+        None
+    }
+}
+
+// The part numbers are in reading order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gear {
+    pub point: Point,
+    pub numbers: Vec<GridNumber>,
+}
+
+impl Gear {
+    pub fn ratio(&self) -> u64 {
+        self.numbers.iter().map(|g| g.value).product()
+    }
+
+    fn checked_ratio(&self) -> Option<u64> {
+        self.numbers.iter().try_fold(1u64, |acc, g| acc.checked_mul(g.value))
+    }
+}
+
+// Everything the solvers read out of the schematic, for comparing against other solvers.
+#[derive(Debug, Serialize)]
+pub struct SchematicDump {
+    pub numbers: Vec<DumpedNumber>,
+    pub symbols: Vec<DumpedSymbol>,
+    pub gears: Vec<DumpedGear>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DumpedNumber {
+    pub value: u64,
+    pub start: Point,
+    pub length: usize,
+    pub part_number: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DumpedSymbol {
+    pub point: Point,
+    pub symbol: char,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DumpedGear {
+    pub point: Point,
+    pub numbers: Vec<u64>,
+    pub ratio: u64,
+}
+
+impl<'a> EngineSchematic<'a> {
+    pub fn new(grid: AoCGrid<'a>) -> Self {
+        let cells = grid.to_grid(|cell| cell.bytes().next().map_or(GridDataType::Space, GridDataType::from));
+        Self { grid, cells }
+    }
+
+    pub fn grid(&self) -> &AoCGrid<'a> {
+        &self.grid
+    }
+
+    pub fn grid_numbers(&self) -> GridNumberIterator<'_> {
+        GridNumberIterator::new(self)
+    }
+
+    pub fn symbols(&self) -> SymbolIterator<'_> {
+        SymbolIterator { engine_schematic: self, points: GridIterator::new(self) }
+    }
+
+    pub fn dump(&self) -> Result<SchematicDump, SchematicError> {
+        let mut numbers = Vec::new();
+        for grid_number in self.grid_numbers() {
+            let grid_number = grid_number?;
+            numbers.push(DumpedNumber {
+                value: grid_number.value,
+                start: grid_number.start_coord,
+                length: grid_number.coord_length,
+                part_number: grid_number.part_number(self).is_some(),
+            });
+        }
+        let symbols = self.symbols().map(|(point, symbol)| DumpedSymbol { point, symbol }).collect();
+        let gears = self.gears()?.map(|g| {
+            let ratio = g.checked_ratio().ok_or(SchematicError::Overflow)?;
+            Ok(DumpedGear { point: g.point, numbers: g.numbers.iter().map(|n| n.value).collect(), ratio })
+        }).collect::<Result<_, SchematicError>>()?;
+        Ok(SchematicDump { numbers, symbols, gears })
+    }
+
+    // Part numbers green, other numbers grey, symbols yellow and gears red.
+    pub fn render(&self) -> Result<String, SchematicError> {
+        let mut colours: Grid<Option<Colour>> = Grid::new(self.width(), self.height(), None);
+        for grid_number in self.grid_numbers() {
+            let grid_number = grid_number?;
+            let colour = if grid_number.part_number(self).is_some() { Colour::Green } else { Colour::Grey };
+            for i in 0..grid_number.coord_length {
+                colours[grid_number.start_coord + Point { x: i, y: 0 }] = Some(colour);
+            }
+        }
+        for (point, _) in self.symbols() {
+            colours[point] = Some(Colour::Yellow);
+        }
+        for gear in self.gears()? {
+            colours[gear.point] = Some(Colour::Red);
+        }
+        Ok(highlight(&self.grid, |p| colours.get(p).copied().flatten()))
+    }
+
+    pub fn gears(&self) -> Result<GearIterator, SchematicError> {
+        self.gears_with(&GearSpec::default())
+    }
+
+    pub fn gears_with(&self, spec: &GearSpec) -> Result<GearIterator, SchematicError> {
+        GearIterator::new(self, spec)
+    }
+}
+
+// Part numbers per row, sorted by start column, so a point resolves with a binary search.
+struct PartNumberIndex {
+    rows: Vec<Vec<GridNumber>>,
+}
+
+impl PartNumberIndex {
+    fn new(engine_schematic: &EngineSchematic) -> Result<Self, SchematicError> {
+        let mut rows = vec![Vec::new(); engine_schematic.height()];
+        for grid_number in engine_schematic.grid_numbers() {
+            let grid_number = grid_number?;
+            if grid_number.part_number(engine_schematic).is_some() {
+                rows[grid_number.start_coord.y].push(grid_number);
+            }
+        }
+        Ok(Self { rows })
+    }
+
+    fn new_parallel(engine_schematic: &EngineSchematic) -> Result<Self, SchematicError> {
+        let rows = (0..engine_schematic.height()).into_par_iter().map(|y| {
+            let mut part_numbers = Vec::new();
+            for grid_number in row_numbers(engine_schematic, y)? {
+                if grid_number.part_number(engine_schematic).is_some() {
+                    part_numbers.push(grid_number);
+                }
+            }
+            Ok(part_numbers)
+        }).collect::<Result<Vec<_>, SchematicError>>()?;
+        Ok(Self { rows })
+    }
+
+    fn get(&self, p: &Point) -> Option<&GridNumber> {
+        let row = self.rows.get(p.y)?;
+        let idx = row.partition_point(|g| g.start_coord.x <= p.x).checked_sub(1)?;
+        row.get(idx).filter(|g| p.x < g.start_coord.x + g.coord_length)
+    }
+}
+
+fn checked_sum<I: IntoIterator<Item = Result<u64, SchematicError>>>(values: I) -> Result<u64, SchematicError> {
+    values.into_iter().try_fold(0u64, |acc, v| acc.checked_add(v?).ok_or(SchematicError::Overflow))
+}
+
+pub fn parse(input: &str) -> Result<EngineSchematic<'_>, SchematicError> {
+    parse_with(input, ParseMode::global())
+}
+
+// Lenient mode drops blank lines around the schematic and trailing whitespace on each row.
+pub fn parse_with(input: &str, mode: ParseMode) -> Result<EngineSchematic<'_>, SchematicError> {
+    let grid = match mode {
+        ParseMode::Strict => AoCGrid::try_new(input)?,
+        ParseMode::Lenient => AoCGrid::try_with_ragged_lines(input.trim_start_matches(['\r', '\n']).trim_end(), RaggedLines::TrimTrailingWhitespace)?,
+    };
+    Ok(EngineSchematic::new(grid))
+}
+
+pub fn solve_one(engine_schematic: &EngineSchematic) -> Result<u64, SchematicError> {
+    solve_one_linear(engine_schematic)
+}
+
+// Enumerates every number, then checks the neighbours of each of its digits.
+pub fn solve_one_by_numbers(engine_schematic: &EngineSchematic) -> Result<u64, SchematicError> {
+    checked_sum(engine_schematic.grid_numbers().map(|n| Ok(n?.part_number(engine_schematic).unwrap_or(0))))
+}
+
+pub fn solve_one_with<F: Fn(char) -> bool>(engine_schematic: &EngineSchematic, is_symbol: F) -> Result<u64, SchematicError> {
+    checked_sum(engine_schematic.grid_numbers().map(|n| Ok(n?.part_number_with(engine_schematic, &is_symbol).unwrap_or(0))))
+}
+
+// One pass over the rows. The window holds which columns have a symbol in the rows above, at and
+// below the current one, so each number is settled as soon as its run of digits ends.
+pub fn solve_one_linear(engine_schematic: &EngineSchematic) -> Result<u64, SchematicError> {
+    let (width, height) = (engine_schematic.width(), engine_schematic.height());
+    let next_row = |y: usize| if y < height { symbol_columns(engine_schematic, y) } else { Ok(vec![false; width]) };
+
+    let mut window = [vec![false; width], next_row(0)?, next_row(1)?];
+    let mut sum: u64 = 0;
+    for y in 0..height {
+        let near_symbol: Vec<bool> = (0..width).map(|x| window.iter().any(|row| row[x])).collect();
+        sum = sum.checked_add(row_part_sum(engine_schematic, y, &near_symbol)?).ok_or(SchematicError::Overflow)?;
+        window.rotate_left(1);
+        window[2] = next_row(y + 2)?;
+    }
+    Ok(sum)
+}
+
+// Numbers only look at the rows either side of their own, so rows can be handed out to threads.
+pub fn solve_one_parallel(engine_schematic: &EngineSchematic) -> Result<u64, SchematicError> {
+    let (width, height) = (engine_schematic.width(), engine_schematic.height());
+    let symbols = (0..height).into_par_iter()
+        .map(|y| symbol_columns(engine_schematic, y))
+        .collect::<Result<Vec<_>, SchematicError>>()?;
+    (0..height).into_par_iter().map(|y| {
+        let rows = &symbols[y.saturating_sub(1)..(y + 2).min(height)];
+        let near_symbol: Vec<bool> = (0..width).map(|x| rows.iter().any(|row| row[x])).collect();
+        row_part_sum(engine_schematic, y, &near_symbol)
+    }).try_reduce(|| 0, |a, b| a.checked_add(b).ok_or(SchematicError::Overflow))
+}
+
+fn symbol_columns(engine_schematic: &EngineSchematic, y: usize) -> Result<Vec<bool>, SchematicError> {
+    (0..engine_schematic.width()).map(|x| {
+        let at = Point { x, y };
+        Ok(cell_type(engine_schematic, at)? == GridDataType::Symbol)
+    }).collect()
+}
+
+// Sum of the numbers on row y touching a column marked in near_symbol, which covers rows y-1..=y+1.
+fn row_part_sum(engine_schematic: &EngineSchematic, y: usize, near_symbol: &[bool]) -> Result<u64, SchematicError> {
+    let mut sum: u64 = 0;
+    let mut add = |value: u64| -> Result<(), SchematicError> {
+        sum = sum.checked_add(value).ok_or(SchematicError::Overflow)?;
+        Ok(())
+    };
+    // (start, value, touching a symbol)
+    let mut current: Option<(usize, u64, bool)> = None;
+    for x in 0..engine_schematic.width() {
+        let at = Point { x, y };
+        match (cell_type(engine_schematic, at)?, current) {
+            (GridDataType::Digit(d), Some((start, value, touching))) => {
+                let value = value.checked_mul(10).and_then(|v| v.checked_add(d))
+                    .ok_or(SchematicError::NumberTooLarge { at: Point { x: start, y } })?;
+                current = Some((start, value, touching || near_symbol[x]));
+            }
+            (GridDataType::Digit(d), None) => {
+                current = Some((x, d, near_symbol[x] || (x > 0 && near_symbol[x - 1])));
+            }
+            (_, Some((_, value, touching))) => {
+                if touching || near_symbol[x] { add(value)?; }
+                current = None;
+            }
+            (_, None) => {}
+        }
+    }
+    if let Some((_, value, true)) = current {
+        add(value)?;
+    }
+    Ok(sum)
+}
+
+fn row_numbers(engine_schematic: &EngineSchematic, y: usize) -> Result<Vec<GridNumber>, SchematicError> {
+    GridNumberIterator::row(engine_schematic, y).collect()
+}
+
+pub fn solve_two(engine_schematic: &EngineSchematic) -> Result<u64, SchematicError> {
+    checked_sum(engine_schematic.gears()?.map(|g| g.checked_ratio().ok_or(SchematicError::Overflow)))
+}
+
+pub fn solve_two_parallel(engine_schematic: &EngineSchematic) -> Result<u64, SchematicError> {
+    let lookup = PartNumberIndex::new_parallel(engine_schematic)?;
+    let spec = GearSpec::default();
+    (0..engine_schematic.height()).into_par_iter().map(|y| {
+        let mut ratios = Vec::new();
+        for x in 0..engine_schematic.width() {
+            let point = Point { x, y };
+            if cell_at(engine_schematic, point)?.starts_with(spec.symbol) {
+                if let Some(gear) = gear_at(engine_schematic, &lookup, point, &spec) {
+                    ratios.push(gear.checked_ratio().ok_or(SchematicError::Overflow));
+                }
+            }
+        }
+        checked_sum(ratios)
+    }).try_reduce(|| 0, |a, b| a.checked_add(b).ok_or(SchematicError::Overflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::strategies;
+    use proptest::prelude::*;
+    use std::fs::read_to_string;
+    fn values(es: &EngineSchematic) -> Vec<u64> {
+        es.grid_numbers().map(|g| g.unwrap().value).collect()
+    }
+
+    const TEST_INPUT: &str = 
+r"467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+    #[test]
+    fn test_grid_iterator() {
+        let es = parse(TEST_INPUT).unwrap();
+        for p in GridIterator::new(&es) {
+            assert!(es.grid().valid_coordinate(&p));
+        }
+    }
+
+    #[test]
+    fn lenient() {
+        let pasted = format!("\n\n{}  \n\n", TEST_INPUT.replace('\n', " \n"));
+        assert!(parse_with(&pasted, ParseMode::Strict).is_err());
+        let es = parse_with(&pasted, ParseMode::Lenient).unwrap();
+        assert_eq!(values(&parse(TEST_INPUT).unwrap()), values(&es));
+    }
+
+    #[test]
+    fn engine_schematic() {
+        let es = parse(TEST_INPUT).unwrap();
+        assert_eq!(vec![467, 114, 35, 633, 617, 58, 592, 755, 664, 598], values(&es));
+    }
+
+    #[test]
+    fn wtf() {
+        let input = r"...123.
+.......";
+        let es = parse(input).unwrap();
+        let v: Vec<u64> = values(&es);
+        assert_eq!(vec![123], v);
+    }
+
+    #[test]
+    fn gridnumberiterator() {
+        let input: &str = r"12.34
+56...
+7..89";
+        let es = parse(input).unwrap();
+        let mut iter = GridNumberIterator::new(&es);
+        assert_eq!(Some(Ok(GridNumber{value: 12, start_coord: Point{x:0,y:0}, coord_length:2})), iter.next());
+        assert_eq!(Some(Ok(GridNumber{value: 34, start_coord: Point{x:3,y:0}, coord_length:2})), iter.next());
+        assert_eq!(Some(Ok(GridNumber{value: 56, start_coord: Point{x:0,y:1}, coord_length:2})), iter.next());
+        assert_eq!(Some(Ok(GridNumber{value: 7, start_coord: Point{x:0,y:2}, coord_length:1})), iter.next());
+        assert_eq!(Some(Ok(GridNumber{value: 89, start_coord: Point{x:3,y:2}, coord_length:2})), iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn adjacent_data() {
+        let input: &str = r"12.34
+56...
+7..89";
+        let es = parse(input).unwrap();
+        let number = es.grid_numbers().nth(1).unwrap().unwrap();
+        let data = GridNumberAdjacentData::new(&number, &es);
+        assert_eq!(8, data.len());
+        use GridDataType::{Digit, Space};
+        assert_eq!(vec![Space, Space, Digit(3), Space, Space, Space, Digit(4), Space], data.rev().map(|(_, cell_type)| cell_type).collect::<Vec<_>>());
+    }
+
+    // The reference: every run of digits, with the box of cells around it searched for symbols.
+    fn naive_solve(input: &str) -> (u64, u64) {
+        let rows: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+        // (row, first column, last column, value)
+        let mut numbers = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                let end = (x..row.len()).find(|&i| !row[i].is_ascii_digit()).unwrap_or(row.len());
+                if end > x {
+                    numbers.push((y, x, end - 1, row[x..end].iter().collect::<String>().parse::<u64>().unwrap()));
+                }
+                x = end + 1;
+            }
+        }
+        let touches = |(y, first, last, _): (usize, usize, usize, u64), (sy, sx): (usize, usize)| sy + 1 >= y && sy <= y + 1 && sx + 1 >= first && sx <= last + 1;
+        let symbols: Vec<(usize, usize, char)> = rows.iter().enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, c)| (y, x, *c)))
+            .filter(|(_, _, c)| *c != '.' && !c.is_ascii_digit())
+            .collect();
+        let one = numbers.iter().filter(|n| symbols.iter().any(|(y, x, _)| touches(**n, (*y, *x)))).map(|n| n.3).sum();
+        let two = symbols.iter().filter(|(_, _, c)| *c == '*').map(|(y, x, _)| {
+            let around: Vec<u64> = numbers.iter().filter(|n| touches(**n, (*y, *x))).map(|n| n.3).collect();
+            if around.len() == 2 { around[0] * around[1] } else { 0 }
+        }).sum();
+        (one, two)
+    }
+
+    proptest! {
+        #[test]
+        fn solvers_match_naive(input in strategies::schematic(12, 10)) {
+            let es = parse(&input).unwrap();
+            let (one, two) = naive_solve(&input);
+            prop_assert_eq!(Ok(one), solve_one(&es));
+            prop_assert_eq!(Ok(one), solve_one_by_numbers(&es));
+            prop_assert_eq!(Ok(two), solve_two(&es));
+        }
+    }
+
+    #[test]
+    fn part_one() {
+        let es = parse(TEST_INPUT).unwrap();
+        assert_eq!(Ok(4361), solve_one(&es));
+    }
+
+    #[test]
+    fn linear_matches_by_numbers() {
+        let inputs = [
+            TEST_INPUT,
+            "12.......*..\n+.........34\n.......-12..\n..78........\n..*....60...\n78.........9\n.5.....23..$\n8...90*12...\n............\n2.2......12.\n.*.........*\n1.1..503+.56",
+            "....................\n..-52..52-..52..52..\n..................-.",
+            ".......5......\n..7*..*.....4*\n...*13*......9\n.......15.....\n21............\n...*9.........",
+            "1*",
+            "*\n9",
+            "99\n..\n.#",
+        ];
+        for input in inputs {
+            let es = parse(input).unwrap();
+            assert_eq!(solve_one_by_numbers(&es), solve_one_linear(&es), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let inputs = [
+            TEST_INPUT,
+            "12.......*..\n+.........34\n.......-12..\n..78........\n..*....60...\n78.........9\n.5.....23..$\n8...90*12...\n............\n2.2......12.\n.*.........*\n1.1..503+.56",
+            ".......5......\n..7*..*.....4*\n...*13*......9\n.......15.....\n21............\n...*9.........",
+            "1*2",
+            "*\n9",
+            "9999999999999999999*9999999999999999999",
+            "*99999999999999999999.",
+        ];
+        for input in inputs {
+            let es = parse(input).unwrap();
+            assert_eq!(solve_one(&es), solve_one_parallel(&es), "{}", input);
+            assert_eq!(solve_two(&es), solve_two_parallel(&es), "{}", input);
+        }
+    }
+
+    #[test]
+    fn dump() {
+        let es = parse("467.\n..*.\n.35.\n...9").unwrap();
+        let dump = es.dump().unwrap();
+        assert_eq!(vec![true, true, false], dump.numbers.iter().map(|n| n.part_number).collect::<Vec<_>>());
+        assert_eq!(
+            r#"{"numbers":[{"value":467,"start":{"x":0,"y":0},"length":3,"part_number":true},{"value":35,"start":{"x":1,"y":2},"length":2,"part_number":true},{"value":9,"start":{"x":3,"y":3},"length":1,"part_number":false}],"symbols":[{"point":{"x":2,"y":1},"symbol":"*"}],"gears":[{"point":{"x":2,"y":1},"numbers":[467,35],"ratio":16345}]}"#,
+            serde_json::to_string(&dump).unwrap());
+    }
+
+    #[test]
+    fn render() {
+        let es = parse("467.\n..*#\n.35.\n...9").unwrap();
+        assert_eq!("\x1b[32m467\x1b[0m.\n..\x1b[31m*\x1b[0m\x1b[33m#\x1b[0m\n.\x1b[32m35\x1b[0m.\n...\x1b[90m9\x1b[0m\n", es.render().unwrap());
+    }
+
+    #[test]
+    fn adjacent_symbols() {
+        let es = parse("#..$\n.12.\n.*..").unwrap();
+        let number = es.grid_numbers().next().unwrap().unwrap();
+        assert_eq!(vec![(Point{x:0,y:0}, '#'), (Point{x:3,y:0}, '$'), (Point{x:1,y:2}, '*')], number.adjacent_symbols(&es));
+        let es = parse(TEST_INPUT).unwrap();
+        let next_to_hash: u64 = es.grid_numbers()
+            .map(|g| g.unwrap())
+            .filter(|g| g.adjacent_symbols(&es).iter().any(|(_, c)| *c == '#'))
+            .map(|g| g.value)
+            .sum();
+        assert_eq!(633, next_to_hash);
+        assert!(es.grid_numbers().nth(1).unwrap().unwrap().adjacent_symbols(&es).is_empty());
+    }
+
+    #[test]
+    fn symbol_predicate() {
+        let es = parse(TEST_INPUT).unwrap();
+        assert_eq!(solve_one(&es), solve_one_with(&es, |_| true));
+        assert_eq!(Ok(3136), solve_one_with(&es, |c| c == '$' || c == '*'));
+        assert_eq!(Ok(0), solve_one_with(&es, |_| false));
+        let number = es.grid_numbers().nth(3).unwrap().unwrap();
+        assert_eq!(Some(633), number.part_number_with(&es, |c| c == '#'));
+        assert_eq!(None, number.part_number_with(&es, |c| c == '*'));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Some(SchematicError::Grid(GridError::RaggedLine { y: 1, expected: 3, found: 2 })), parse("1..\n.*").err());
+        let es = parse("*99999999999999999999.\n..1*..................").unwrap();
+        let too_large = SchematicError::NumberTooLarge { at: Point{x:1,y:0} };
+        assert_eq!(Some(Err(too_large.clone())), es.grid_numbers().next());
+        assert_eq!(Err(too_large.clone()), solve_one_linear(&es));
+        assert_eq!(Err(too_large.clone()), solve_one_by_numbers(&es));
+        assert_eq!(Some(too_large), solve_two(&es).err());
+        let es = parse("9999999999999999999*9999999999999999999").unwrap();
+        assert_eq!(Err(SchematicError::Overflow), solve_one(&es));
+        assert_eq!(Err(SchematicError::Overflow), solve_two(&es));
+    }
+
+    #[test]
+    fn part_two() {
+        let es = parse(TEST_INPUT).unwrap();
+        assert_eq!(Ok(467835), solve_two(&es));
+    }
+
+    #[test]
+    fn gear_api() {
+        let es = parse(TEST_INPUT).unwrap();
+        let gears: Vec<Gear> = es.gears().unwrap().collect();
+        assert_eq!(2, gears.len());
+        assert_eq!(Point{x:3,y:1}, gears[0].point);
+        assert_eq!(vec![467, 35], gears[0].numbers.iter().map(|g| g.value).collect::<Vec<_>>());
+        assert_eq!(16345, gears[0].ratio());
+        assert_eq!(Point{x:5,y:8}, gears[1].point);
+        assert_eq!(GridNumber{value: 598, start_coord: Point{x:5,y:9}, coord_length: 3}, gears[1].numbers[1]);
+        assert_eq!(451490, gears[1].ratio());
+    }
+
+    #[test]
+    fn symbols() {
+        let es = parse(TEST_INPUT).unwrap();
+        let symbols: Vec<(Point, char)> = es.symbols().collect();
+        assert_eq!(6, symbols.len());
+        assert_eq!((Point{x:3,y:1}, '*'), symbols[0]);
+        assert_eq!(vec!['*', '#', '*', '+', '$', '*'], symbols.iter().map(|(_, c)| *c).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn part_number_index() {
+        let es = parse(TEST_INPUT).unwrap();
+        let index = PartNumberIndex::new(&es).unwrap();
+        assert_eq!(Some(467), index.get(&Point{x:0,y:0}).map(|g| g.value));
+        assert_eq!(Some(467), index.get(&Point{x:2,y:0}).map(|g| g.value));
+        assert_eq!(None, index.get(&Point{x:3,y:0}));
+        assert_eq!(None, index.get(&Point{x:5,y:0}));
+        assert_eq!(Some(633), index.get(&Point{x:8,y:2}).map(|g| g.value));
+        assert_eq!(None, index.get(&Point{x:8,y:5}));
+        assert_eq!(None, index.get(&Point{x:0,y:10}));
+    }
+
+    #[test]
+    fn gear_spec() {
+        let input: &str = r"1.2.3
+.#.*.
+4.5..
+6..*7";
+        let es = parse(input).unwrap();
+        let ratios = |spec: GearSpec| es.gears_with(&spec).unwrap().map(|g| g.ratio()).collect::<Vec<_>>();
+        assert_eq!(vec![35], ratios(GearSpec::default()));
+        assert_eq!(vec![30], ratios(GearSpec { symbol: '*', arity: Arity::AtLeast(3) }));
+        assert_eq!(vec![30, 35], ratios(GearSpec { symbol: '*', arity: Arity::AtLeast(2) }));
+        assert_eq!(vec![40], ratios(GearSpec { symbol: '#', arity: Arity::Exactly(4) }));
+        assert_eq!(Vec::<u64>::new(), ratios(GearSpec { symbol: '$', arity: Arity::AtLeast(0) }));
+    }
+
+    #[test]
+    fn part_one_bigger() {
+        let input: &str = 
+r".........232.633.......................803..........................361................192............539.................973.221...340.....
+.............*..............#.....256.#.........329....................*313............*.......766.......*..........472..-...........+..249.
+670-..@.......181......814..865.........968......@.......605....128.............%......798.638...+....776...........*......%...........*....";
+        let es = parse(input).unwrap();
+        assert_eq!(vec![232,633,803,361,192,539,973,221,340,256,329,313,766,472,249,670,181,814,865,968,605,128,798,638,776], values(&es));
+    }
+
+    #[test]
+    fn start_end() {
+        let input: &str = r"12.34
+56...
+7..89";
+        let es = parse(input).unwrap();
+        assert_eq!(vec![12,34,56,7,89], values(&es));
+    }
+
+    #[test]
+    fn datatype() {
+        assert_eq!(GridDataType::Symbol, "$".parse().unwrap());
+        assert_eq!(GridDataType::Digit(7), GridDataType::from(b'7'));
+        assert_eq!(GridDataType::Space, GridDataType::from(b'.'));
+        assert!("12".parse::<GridDataType>().is_err());
+        let es = parse(TEST_INPUT).unwrap();
+        assert_eq!(Some(&GridDataType::Symbol), es.cells.get(&Point{x:6,y:3}));
+        assert_eq!(vec![617], GridNumberIterator::row(&es, 4).map(|g| g.unwrap().value).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reddit() {
+        let input: &str =
+r"12.......*..
++.........34
+.......-12..
+..78........
+..*....60...
+78.........9
+.5.....23..$
+8...90*12...
+............
+2.2......12.
+.*.........*
+1.1..503+.56";
+        let es = parse(input).unwrap();
+        assert_eq!(Ok(925), solve_one(&es));
+    }
+
+    #[test]
+    fn reddit2() {
+        let input: &str = r"........
+.24..4..
+......*.";
+        let es = parse(input).unwrap();
+        assert_eq!(2, es.grid_numbers().count());
+    }
+
+    #[test]
+    fn reddit3() {
+        let input: &str = r"....................
+..-52..52-..52..52..
+..................-.";
+        let es = parse(input).unwrap();
+        assert_eq!(4, es.grid_numbers().count());
+        assert_eq!(Ok(156), solve_one(&es))
+    }
+
+    #[test]
+    fn reddit4() {
+        let input: &str = r".......5......
+..7*..*.....4*
+...*13*......9
+.......15.....
+..............
+..............
+..............
+..............
+..............
+..............
+21............
+...*9.........";
+        let es = parse(input).unwrap();
+        assert_eq!(Ok(62), solve_one(&es));
+    }
+
+    #[test]
+    fn detects_all_numbers_from_large_input() {
+        let input = read_to_string("input.txt").expect("Read input data");
+        let es = parse(&input).unwrap();
+        assert_eq!(vec![232,633,803,361,192,539,973,221,340,256,329,313,766,472,249,670,181,814,865,968,605,128,798,638,776,563,741,815,921,428,219,993,584,990,431,466,971,815,634,197,887,114,521,796,713,546,941,837,903,910,988,61,946,240,697,563,707,895,223,160,618,61,603,495,633,697,910,70,497,568,832,551,863,324,837,701,740,72,98,245,145,832,580,432,315,4,174,971,76,472,66,260,348,179,908,108,726,654,422,501,644,279,528,913,639,131,5,228,900,148,665,220,561,237,576,381,771,416,996,799,441,355,570,481,422,798,924,462,420,659,404,233,955,265,86,43,1,398,624,896,53,855,301,688,614,103,486,672,725,508,993,906,124,92,208,626,298,810,428,461,619,590,636,683,128,524,507,636,991,52,61,993,627,796,841,105,313,555,715,625,963,194,505,841,442,108,58,450,343,138,560,561,53,327,766,234,276,370,913,16,825,111,561,446,372,8,136,758,349,666,340,639,214,364,31,440,644,577,382,175,84,931,692,860,400,235,797,863,683,778,367,79,192,897,320,634,32,556,783,475,781,875,891,867,322,8,938,318,462,620,293,330,26,668,205,32,975,750,25,521,388,116,33,2,519,717,859,881,109,828,927,240,66,27,482,227,968,479,91,598,102,615,184,456,385,476,13,391,526,90,500,14,206,57,53,134,784,775,692,88,873,115,9,937,242,729,342,344,568,140,521,185,462,331,337,90,829,262,376,787,352,227,413,518,796,698,346,277,918,902,327,120,320,902,488,150,688,822,721,3,445,132,71,880,770,150,674,924,746,403,929,771,110,63,847,423,651,729,927,867,577,763,55,320,674,962,421,707,222,301,702,943,431,59,600,756,593,352,579,965,607,669,406,704,720,333,839,449,210,219,84,842,582,350,831,394,835,184,676,755,22,710,86,889,86,625,195,547,549,945,601,975,285,743,433,619,675,204,161,493,896,576,328,902,819,362,373,854,272,812,933,447,950,124,990,172,139,530,27,844,486,810,826,880,359,242,432,206,519,805,66,859,943,742,116,421,984,559,566,790,372,307,180,532,135,88,417,576,138,314,776,670,893,565,985,833,369,372,842,868,221,168,128,500,962,31,143,897,727,42,24,382,593,414,165,179,42,468,362,39,802,339,240,257,386,262,556,852,670,872,480,945,983,604,997,182,916,800,165,927,55,521,394,142,672,967,107,785,208,614,386,975,923,273,146,70,177,550,606,430,35,707,157,334,675,719,762,960,343,498,291,654,592,54,500,772,252,689,357,778,273,455,381,117,388,386,258,948,914,514,476,975,274,119,56,94,390,250,484,723,415,451,2,115,818,859,401,240,205,228,757,102,954,863,523,613,844,832,35,989,381,827,702,592,456,385,78,233,27,49,574,230,311,326,617,585,798,699,20,687,662,246,735,61,361,171,380,786,378,624,836,742,322,195,634,422,893,106,960,121,969,738,919,78,685,773,654,414,297,44,718,718,841,446,881,825,870,104,341,663,292,165,466,892,296,948,748,99,707,339,483,896,241,494,227,821,761,143,329,24,54,202,588,481,203,37,90,390,171,80,857,689,930,794,233,503,62,14,438,149,492,842,721,301,958,265,628,9,813,671,518,903,974,120,269,560,907,214,961,22,740,825,612,740,307,467,350,535,665,138,831,487,432,348,32,529,395,318,984,492,835,735,333,136,779,848,486,736,507,329,189,745,223,552,888,53,415,930,845,3,99,599,731,986,582,669,367,969,132,13,420,442,793,997,908,148,961,397,9,144,736,942,346,970,72,794,608,13,993,462,539,560,637,208,896,5,856,178,727,787,593,736,191,609,774,62,325,372,994,513,853,907,43,511,850,49,696,856,651,314,213,718,70,659,453,123,921,11,32,312,795,467,689,760,997,545,597,808,565,115,522,179,606,269,885,733,857,857,252,510,842,518,678,420,426,92,447,995,761,158,128,178,495,748,927,411,519,430,480,667,266,846,625,807,561,687,268,55,60,556,56,67,698,593,485,166,174,944,591,808,698,376,891,951,538,563,472,584,460,492,716,238,297,23,90,668,798,351,720,513,476,312,745,35,550,885,343,937,435,882,556,417,691,671,609,424,675,650,9,900,867,975,897,905,122,555,796,530,229,585,22,456,495,107,800,683,876,181,954,774,643,437,310,494,265,320,13,258,632,677,227,922,778,384,908,156,533,192,420,861,771,553,860,370,309,483,500,773,900,249,93,171,248,315,470,169,198,482,771,267,835,6,459,252,496,334,493,991,191,521,661,514,832,296,650,363,442,942,58,390,865,16,586,993,255,55,337,334,82,490,5,381,16,867,35,427,877,768,110,413,104,90,623,433,462,33,685,228,288,513,721,717,344,970,953,546,162,637,37,740,331,564,556,843,195,2,937,255,349,837,342,411,537,337,791,641,424,24,261,667,551,324,330,841,465,486,996,227,33,681,354,455,304,542,64,624,716,245,166,331,738,249,126,833,913,690,943,284,938,224,429,195,231,857,975,252,71,599,279,828,967,285,798,370,640,898,746,134,329,768,279,840,979,374,192,370,964,970,436,410,306,727,139,689,819,498,982,131,566,390,505,84,973,830,394,401,562,907,405,321,455,284,722,124,921,303,652,286,775,274,74,774,986,96,469,335,526,344,31,942,31,846,72,582,380,570,201,648,838,253,101,606,744,792,396,990,609,938,896,125,842,485,510,801,329,983,963,761,927,45,981,675,676,156,30,998,697,14,366,960,874,497,278],
+        values(&es));
+    }
+
+    #[test]
+    fn weird() {
+        let input = r"................713.546......*........941......*..*..837............903...............910.........988....61..........&..946..240......697...";
+        let es = parse(input).unwrap();
+        assert_eq!(vec![713,546,941,837,903,910,988,61,946,240,697], values(&es));
+
+    }
+
+
+}
@@ -0,0 +1,156 @@
+use std::io::BufRead;
+
+use common::grid::{GridError, Point};
+use common::parse::ParseMode;
+
+use crate::{GridDataType, SchematicError};
+
+// Only three rows are ever held, so the input can be far larger than memory.
+struct StreamRow {
+    bytes: Vec<u8>,
+    // (start, end exclusive, value)
+    numbers: Vec<(usize, usize, u64)>,
+}
+
+impl StreamRow {
+    fn new(y: usize, line: String) -> Result<Self, SchematicError> {
+        if let Some((x, c)) = line.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+            return Err(GridError::NonAscii { c, at: Point { x, y } }.into());
+        }
+        let bytes = line.into_bytes();
+        let mut numbers = Vec::new();
+        let mut x = 0;
+        while x < bytes.len() {
+            if !bytes[x].is_ascii_digit() {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            let mut value: Option<u64> = Some(0);
+            while x < bytes.len() && bytes[x].is_ascii_digit() {
+                value = value.and_then(|v| v.checked_mul(10)).and_then(|v| v.checked_add((bytes[x] - b'0') as u64));
+                x += 1;
+            }
+            let value = value.ok_or(SchematicError::NumberTooLarge { at: Point { x: start, y } })?;
+            numbers.push((start, x, value));
+        }
+        Ok(Self { bytes, numbers })
+    }
+}
+
+fn is_symbol(b: u8) -> bool {
+    GridDataType::from(b) == GridDataType::Symbol
+}
+
+fn part_sum(current: &StreamRow, window: &[Option<&StreamRow>; 3]) -> Result<u64, SchematicError> {
+    let width = current.bytes.len();
+    let mut sum: u64 = 0;
+    for (start, end, value) in current.numbers.iter() {
+        let columns = start.saturating_sub(1)..(end + 1).min(width);
+        if window.iter().flatten().any(|row| row.bytes[columns.clone()].iter().any(|b| is_symbol(*b))) {
+            sum = sum.checked_add(*value).ok_or(SchematicError::Overflow)?;
+        }
+    }
+    Ok(sum)
+}
+
+fn gear_sum(current: &StreamRow, window: &[Option<&StreamRow>; 3]) -> Result<u64, SchematicError> {
+    let mut sum: u64 = 0;
+    for x in (0..current.bytes.len()).filter(|x| current.bytes[*x] == b'*') {
+        let adjacent: Vec<u64> = window.iter().flatten()
+            .flat_map(|row| row.numbers.iter())
+            .filter(|(start, end, _)| *start <= x + 1 && *end >= x)
+            .map(|(_, _, value)| *value)
+            .collect();
+        if let [a, b] = adjacent[..] {
+            let ratio = a.checked_mul(b).ok_or(SchematicError::Overflow)?;
+            sum = sum.checked_add(ratio).ok_or(SchematicError::Overflow)?;
+        }
+    }
+    Ok(sum)
+}
+
+// Part one and part two together, reading the schematic a row at a time.
+pub fn solve_streaming<R: BufRead>(reader: R) -> Result<(u64, u64), SchematicError> {
+    solve_streaming_with(reader, ParseMode::global())
+}
+
+// Lenient mode skips blank lines and trailing whitespace on each row, so rows are numbered
+// from the first one kept, as parse_with does.
+pub fn solve_streaming_with<R: BufRead>(reader: R, mode: ParseMode) -> Result<(u64, u64), SchematicError> {
+    let mut lines = reader.lines();
+    let mut width: Option<usize> = None;
+    let mut next_row = |y: usize| -> Result<Option<StreamRow>, SchematicError> {
+        let line = loop {
+            let Some(line) = lines.next() else { return Ok(None) };
+            let mut line = line.map_err(|e| SchematicError::Read(e.to_string()))?;
+            if let Some(len) = mode.line(&line).map(str::len) {
+                line.truncate(len);
+                break line;
+            }
+        };
+        let row = StreamRow::new(y, line)?;
+        let expected = *width.get_or_insert(row.bytes.len());
+        if row.bytes.len() != expected {
+            return Err(GridError::RaggedLine { y, expected, found: row.bytes.len() }.into());
+        }
+        Ok(Some(row))
+    };
+
+    let (mut one, mut two): (u64, u64) = (0, 0);
+    let mut above: Option<StreamRow> = None;
+    let mut current = next_row(0)?.ok_or(SchematicError::Grid(GridError::Empty))?;
+    let mut y = 0;
+    loop {
+        let below = next_row(y + 1)?;
+        let window = [above.as_ref(), Some(&current), below.as_ref()];
+        one = one.checked_add(part_sum(&current, &window)?).ok_or(SchematicError::Overflow)?;
+        two = two.checked_add(gear_sum(&current, &window)?).ok_or(SchematicError::Overflow)?;
+        match below {
+            Some(below) => above = Some(std::mem::replace(&mut current, below)),
+            None => return Ok((one, two)),
+        }
+        y += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, solve_one, solve_two};
+
+    #[test]
+    fn matches_in_memory() {
+        let inputs = [
+            "467..114..\n...*......\n..35..633.\n......#...\n617*......\n.....+.58.\n..592.....\n......755.\n...$.*....\n.664.598..",
+            "12.......*..\n+.........34\n.......-12..\n..78........\n..*....60...\n78.........9\n.5.....23..$\n8...90*12...\n............\n2.2......12.\n.*.........*\n1.1..503+.56",
+            ".......5......\n..7*..*.....4*\n...*13*......9\n.......15.....\n21............\n...*9.........",
+            "1*2",
+            "*\n9\n",
+            "2.2\n.*.\n2.2",
+        ];
+        for input in inputs {
+            let es = parse(input).unwrap();
+            let expected = (solve_one(&es).unwrap(), solve_two(&es).unwrap());
+            assert_eq!(Ok(expected), solve_streaming_with(input.as_bytes(), ParseMode::Strict), "{}", input);
+        }
+    }
+
+    #[test]
+    fn lenient() {
+        let pasted = "\n467..114..  \n...*......\n..35..633.\t\n\n";
+        let es = crate::parse_with(pasted, ParseMode::Lenient).unwrap();
+        let expected = (solve_one(&es).unwrap(), solve_two(&es).unwrap());
+        assert_eq!(Ok(expected), solve_streaming_with(pasted.as_bytes(), ParseMode::Lenient));
+        assert_eq!(Err(SchematicError::Grid(GridError::RaggedLine { y: 1, expected: 0, found: 12 })), solve_streaming_with(pasted.as_bytes(), ParseMode::Strict));
+        let ragged = solve_streaming_with("1.\n\n.*\n.".as_bytes(), ParseMode::Lenient);
+        assert_eq!(Err(SchematicError::Grid(GridError::RaggedLine { y: 2, expected: 2, found: 1 })), ragged);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err(SchematicError::Grid(GridError::Empty)), solve_streaming_with("".as_bytes(), ParseMode::Strict));
+        assert_eq!(Err(SchematicError::Grid(GridError::RaggedLine { y: 2, expected: 2, found: 1 })), solve_streaming_with("1.\n.*\n.".as_bytes(), ParseMode::Strict));
+        assert_eq!(Err(SchematicError::NumberTooLarge { at: Point { x: 1, y: 1 } }), solve_streaming_with("*.....................\n.99999999999999999999.".as_bytes(), ParseMode::Strict));
+    }
+}
@@ -0,0 +1,51 @@
+use std::fs::read_to_string;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use two::{parse_games, solve_folding, solve_one, solve_two};
+
+// Games shaped like the real input, from a fixed linear congruential sequence so every run times
+// the same text.
+fn synthetic_input(games: usize) -> String {
+    let mut state: u64 = 2023;
+    let mut next = |bound: u64| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) % bound
+    };
+    let mut input = String::new();
+    for id in 1..=games {
+        let mut drawings: Vec<String> = Vec::new();
+        for _ in 0..=next(5) {
+            let mut counts: Vec<String> = Vec::new();
+            for colour in ["red", "green", "blue"] {
+                if next(3) > 0 {
+                    counts.push(format!("{} {}", next(20) + 1, colour));
+                }
+            }
+            drawings.push(if counts.is_empty() { "1 red".to_string() } else { counts.join(", ") });
+        }
+        input.push_str(&format!("Game {}: {}\n", id, drawings.join("; ")));
+    }
+    input
+}
+
+fn bench_input(c: &mut Criterion, label: &str, input: &str) {
+    let mut group = c.benchmark_group(label);
+    group.bench_function("parse games", |b| b.iter(|| {
+        let games = parse_games(black_box(input)).unwrap();
+        (solve_one(&games), solve_two(&games))
+    }));
+    group.bench_function("fold", |b| b.iter(|| solve_folding(black_box(input)).unwrap()));
+    group.finish();
+}
+
+fn parsing(c: &mut Criterion) {
+    bench_input(c, "synthetic", &synthetic_input(10_000));
+    if let Ok(input) = read_to_string("input.txt") {
+        bench_input(c, "input", &input);
+    }
+}
+
+criterion_group!(benches, parsing);
+criterion_main!(benches);
@@ -0,0 +1,78 @@
+use crate::Drawing;
+
+// Decides whether a single drawing could have come out of the bag. A game is possible when
+// every one of its drawings is allowed.
+pub trait Criterion {
+    fn allows(&self, drawing: &Drawing) -> bool;
+
+    fn and<C: Criterion>(self, other: C) -> And<Self, C> where Self: Sized {
+        And(self, other)
+    }
+
+    fn or<C: Criterion>(self, other: C) -> Or<Self, C> where Self: Sized {
+        Or(self, other)
+    }
+}
+
+// Closures still work for one-off rules.
+impl<F: Fn(&Drawing) -> bool> Criterion for F {
+    fn allows(&self, drawing: &Drawing) -> bool {
+        self(drawing)
+    }
+}
+
+// The bag holds at most this many cubes of each colour, and none of any colour not listed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaxPerColour(pub Drawing);
+
+impl Criterion for MaxPerColour {
+    fn allows(&self, drawing: &Drawing) -> bool {
+        drawing.fits_within(&self.0)
+    }
+}
+
+// No more than this many cubes are drawn at once, whatever their colours.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TotalCubes(pub u64);
+
+impl Criterion for TotalCubes {
+    fn allows(&self, drawing: &Drawing) -> bool {
+        drawing.total() <= self.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: Criterion, B: Criterion> Criterion for And<A, B> {
+    fn allows(&self, drawing: &Drawing) -> bool {
+        self.0.allows(drawing) && self.1.allows(drawing)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: Criterion, B: Criterion> Criterion for Or<A, B> {
+    fn allows(&self, drawing: &Drawing) -> bool {
+        self.0.allows(drawing) || self.1.allows(drawing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{part_one_limits, BLUE, GREEN, RED};
+
+    #[test]
+    fn criteria() {
+        let drawing = Drawing::new([(RED, 10), (GREEN, 13), (BLUE, 1)]);
+        assert!(MaxPerColour(part_one_limits()).allows(&drawing));
+        assert!(!MaxPerColour(Drawing::new([(RED, 20), (GREEN, 20)])).allows(&drawing));
+        assert!(TotalCubes(24).allows(&drawing));
+        assert!(!TotalCubes(23).allows(&drawing));
+        assert!(!MaxPerColour(part_one_limits()).and(TotalCubes(20)).allows(&drawing));
+        assert!(MaxPerColour(Drawing::default()).or(TotalCubes(30)).allows(&drawing));
+        assert!(!TotalCubes(3).or(|d: &Drawing| d.get(BLUE) > 1).allows(&drawing));
+    }
+}
@@ -0,0 +1,41 @@
+// The games grammar written with the shared nom combinators, as a reference for days whose
+// input is too involved for split_once. Reads well-formed lines just as Game::from_str does.
+use common::combinators::{labelled, list, number, parse_all};
+use nom::bytes::complete::{is_not, tag};
+use nom::combinator::{map, verify};
+use nom::error::Error;
+use nom::sequence::{separated_pair, terminated};
+use nom::IResult;
+
+use crate::{ColorId, Drawing, Game};
+
+fn drawing(input: &str) -> IResult<&str, Drawing> {
+    let count = separated_pair(number, tag(" "), map(verify(is_not(",;"), |c: &str| c.trim() == c), ColorId::intern));
+    map(list(", ", count), |counts| Drawing::new(counts.into_iter().map(|(n, colour)| (colour, n))))(input)
+}
+
+pub fn game(input: &str) -> IResult<&str, Game> {
+    let (input, id) = labelled("Game ", terminated(number, tag(": ")))(input)?;
+    let (input, drawings) = list("; ", drawing)(input)?;
+    Ok((input, Game { id, drawings }))
+}
+
+pub fn parse_game(line: &str) -> Result<Game, Error<String>> {
+    parse_all(game, line)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_from_str() {
+        for line in ["Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green", "Game 12: 1 dark orange", "Game 3: 0 red"] {
+            assert_eq!(line.parse::<Game>().unwrap(), parse_game(line).unwrap());
+        }
+        for line in ["Game 1 3 blue", "Game x: 3 blue", "Game 1: 3", "Game 1: blue", "Game 1: 3 blue, ", "Game 1: 3 blue  "] {
+            assert!(line.parse::<Game>().is_err());
+            assert!(parse_game(line).is_err());
+        }
+    }
+}
@@ -0,0 +1,593 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::io::BufRead;
+use std::sync::{Mutex, MutexGuard};
+
+use common::diagnostic::{Diagnostic, ToDiagnostic};
+use common::aoc_parse;
+use common::error::AocError;
+use common::parse::{FormatError, ParseMode};
+use serde::Serialize;
+use serde::ser::{SerializeMap, Serializer};
+
+pub mod criterion;
+#[cfg(feature = "nom")]
+pub mod grammar;
+
+use criterion::{Criterion, MaxPerColour};
+
+// An interned colour name. The puzzle's three colours are always the first three.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ColorId(pub usize);
+
+pub const RED: ColorId = ColorId(0);
+pub const GREEN: ColorId = ColorId(1);
+pub const BLUE: ColorId = ColorId(2);
+pub const STANDARD_COLOURS: [ColorId; 3] = [RED, GREEN, BLUE];
+
+static COLOUR_NAMES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn colour_names() -> MutexGuard<'static, Vec<String>> {
+    let mut names = COLOUR_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    if names.is_empty() {
+        names.extend(["red", "green", "blue"].map(String::from));
+    }
+    names
+}
+
+impl ColorId {
+    pub fn intern(name: &str) -> Self {
+        let mut names = colour_names();
+        match names.iter().position(|n| n == name) {
+            Some(i) => ColorId(i),
+            None => {
+                names.push(name.to_string());
+                ColorId(names.len() - 1)
+            }
+        }
+    }
+
+    pub fn name(&self) -> String {
+        colour_names().get(self.0).cloned().unwrap_or_default()
+    }
+}
+
+// Colours that were not drawn are left out rather than stored as zero, so equal drawings compare
+// equal however they were written.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Drawing {
+    counts: HashMap<ColorId, u64>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseDrawingError {
+    MissingColour(String),
+    InvalidCount(String),
+    InvalidColour(String),
+}
+
+impl fmt::Display for ParseDrawingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDrawingError::MissingColour(s) => write!(f, "{:?} is not a count and a colour", s),
+            ParseDrawingError::InvalidCount(s) => write!(f, "{:?} is not a valid count", s),
+            ParseDrawingError::InvalidColour(s) => write!(f, "{:?} is not a colour", s),
+        }
+    }
+}
+
+impl FromStr for Drawing {
+    type Err = ParseDrawingError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut counts: Vec<(ColorId, u64)> = Vec::new();
+
+        for elem in s.split(", ") {
+            let (num, col) = elem.split_once(' ').ok_or_else(|| ParseDrawingError::MissingColour(elem.to_string()))?;
+            let n: u64 = num.parse().map_err(|_| ParseDrawingError::InvalidCount(num.to_string()))?;
+            counts.push((ColorId::intern(colour_name(col)?), n));
+        }
+
+        Ok(Self::new(counts))
+    }
+}
+
+// One or more words with nothing around them, so stray trailing spaces aren't taken as part of it.
+fn colour_name(col: &str) -> Result<&str, ParseDrawingError> {
+    if col.is_empty() || col.trim() != col { Err(ParseDrawingError::InvalidColour(col.to_string())) } else { Ok(col) }
+}
+
+// The bag from part one.
+pub fn part_one_limits() -> Drawing {
+    Drawing::new([(RED, 12), (GREEN, 13), (BLUE, 14)])
+}
+
+impl Drawing {
+    pub fn new<I: IntoIterator<Item = (ColorId, u64)>>(counts: I) -> Self {
+        Self { counts: counts.into_iter().filter(|(_, n)| *n > 0).collect() }
+    }
+
+    pub fn get(&self, colour: ColorId) -> u64 {
+        self.counts.get(&colour).copied().unwrap_or(0)
+    }
+
+    pub fn counts(&self) -> impl Iterator<Item = (ColorId, u64)> + '_ {
+        self.counts.iter().map(|(colour, n)| (*colour, *n))
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    pub fn is_possible(&self, criterion: &dyn Criterion) -> bool {
+        criterion.allows(self)
+    }
+
+    // How many cubes of each colour go beyond limits; empty when the drawing fits.
+    pub fn excess(&self, limits: &Drawing) -> Drawing {
+        Drawing::new(self.counts().map(|(colour, n)| (colour, n.saturating_sub(limits.get(colour)))))
+    }
+
+    // Whether a bag holding limits could have produced this drawing. Colours missing from limits
+    // are not in the bag at all.
+    pub fn fits_within(&self, limits: &Drawing) -> bool {
+        self.counts.iter().all(|(colour, n)| *n <= limits.get(*colour))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Game {
+    pub id: u64,
+    pub drawings: Vec<Drawing>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseGameError {
+    MissingColon,
+    InvalidId(String),
+    Drawing(ParseDrawingError),
+    Read(String),
+}
+
+impl fmt::Display for ParseGameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseGameError::MissingColon => write!(f, "expected \"Game N: \" before the drawings"),
+            ParseGameError::InvalidId(s) => write!(f, "{:?} is not a valid game ID", s),
+            ParseGameError::Drawing(e) => write!(f, "{}", e),
+            ParseGameError::Read(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+aoc_parse!(Game: ParseGameError = "Game " {exact id} ": " {exact drawings: "; "});
+
+// In the terms the diagnostics use: whatever comes before the colon is taken as the ID, and a
+// drawing is parsed again for its own error.
+impl From<FormatError> for ParseGameError {
+    fn from(e: FormatError) -> Self {
+        match e {
+            FormatError::Expected { literal: "Game ", found, .. } => match found.split_once(": ") {
+                Some((id, _)) => ParseGameError::InvalidId(id.to_string()),
+                None => ParseGameError::MissingColon,
+            },
+            FormatError::Expected { .. } | FormatError::TrailingText { .. } => ParseGameError::MissingColon,
+            FormatError::InvalidField { field: "id", text, .. } => ParseGameError::InvalidId(text),
+            FormatError::InvalidField { text, .. } => {
+                ParseGameError::Drawing(text.parse::<Drawing>().expect_err("aoc_parse! only reports drawings that don't parse"))
+            },
+        }
+    }
+}
+
+// Lines are numbered from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseGamesError {
+    pub line: usize,
+    pub error: ParseGameError,
+}
+
+impl fmt::Display for ParseGamesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl Error for ParseGamesError {}
+
+impl From<ParseGamesError> for AocError {
+    fn from(e: ParseGamesError) -> Self {
+        AocError::Parse(Diagnostic::at_line(e.line, &e.error.to_string()))
+    }
+}
+
+impl ToDiagnostic for ParseGamesError {
+    fn to_diagnostic(&self, source: &str) -> Diagnostic {
+        let message = self.error.to_string();
+        match &self.error {
+            ParseGameError::MissingColon => Diagnostic::whole_line(source, self.line, &message, "no \"Game N: \""),
+            ParseGameError::InvalidId(s) => Diagnostic::at_token(source, self.line, s, &message, "not a game ID"),
+            ParseGameError::Drawing(ParseDrawingError::MissingColour(s)) => Diagnostic::at_token(source, self.line, s, &message, "not a count and colour"),
+            ParseGameError::Drawing(ParseDrawingError::InvalidCount(s)) => Diagnostic::at_token(source, self.line, s, &message, "not a count"),
+            ParseGameError::Drawing(ParseDrawingError::InvalidColour(s)) => Diagnostic::at_token(source, self.line, s, &message, "not a colour"),
+            ParseGameError::Read(_) => Diagnostic::whole_line(source, self.line, &message, "unreadable"),
+        }
+    }
+}
+
+// The same form the input uses, e.g. "4 red, 3 blue", in the order the colours were first seen.
+impl fmt::Display for Drawing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut counts: Vec<(ColorId, u64)> = self.counts().collect();
+        counts.sort();
+        let parts: Vec<String> = counts.iter().map(|(colour, n)| format!("{} {}", n, colour.name())).collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+// Written as an object from colour name to count, in the order the colours were first seen.
+impl Serialize for Drawing {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut counts: Vec<(&ColorId, &u64)> = self.counts.iter().collect();
+        counts.sort();
+        let mut map = serializer.serialize_map(Some(counts.len()))?;
+        for (colour, n) in counts {
+            map.serialize_entry(&colour.name(), n)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct GameStats {
+    pub id: u64,
+    pub drawings: usize,
+    pub possible: bool,
+    pub minimum_bag: Drawing,
+    pub power: u64,
+    pub totals: Drawing,
+    pub total_cubes: u64,
+}
+
+impl Game {
+    pub fn drawing_count(&self) -> usize {
+        self.drawings.len()
+    }
+
+    // Every cube of each colour shown across all the drawings.
+    pub fn totals(&self) -> Drawing {
+        let mut totals: HashMap<ColorId, u64> = HashMap::new();
+        for (colour, n) in self.drawings.iter().flat_map(|d| d.counts.iter()) {
+            *totals.entry(*colour).or_insert(0) += n;
+        }
+        Drawing::new(totals)
+    }
+
+    pub fn stats(&self, criterion: &dyn Criterion) -> GameStats {
+        let totals = self.totals();
+        GameStats {
+            id: self.id,
+            drawings: self.drawing_count(),
+            possible: self.is_possible(criterion),
+            minimum_bag: self.minimum_bag(),
+            power: self.power(),
+            total_cubes: totals.total(),
+            totals,
+        }
+    }
+
+    pub fn is_possible(&self, criterion: &dyn Criterion) -> bool {
+        self.drawings.iter().all(|d| d.is_possible(criterion))
+    }
+
+    // The drawings, with their positions, that a bag holding limits could not have produced.
+    pub fn violations<'a>(&'a self, limits: &'a Drawing) -> impl Iterator<Item = (usize, &'a Drawing)> + 'a {
+        self.drawings.iter().enumerate().filter(move |(_, d)| !d.fits_within(limits))
+    }
+
+    // The fewest cubes of each colour that make every drawing possible.
+    pub fn minimum_bag(&self) -> Drawing {
+        let mut maxima: HashMap<ColorId, u64> = HashMap::new();
+        for (colour, n) in self.drawings.iter().flat_map(|d| d.counts.iter()) {
+            let max = maxima.entry(*colour).or_insert(0);
+            *max = (*max).max(*n);
+        }
+        Drawing::new(maxima)
+    }
+
+    // Multiplies over the puzzle's colours and any others this game drew, so a game that never
+    // shows one of red, green or blue still has no power.
+    pub fn power(&self) -> u64 {
+        let bag = self.minimum_bag();
+        let extra = bag.counts.keys().filter(|c| !STANDARD_COLOURS.contains(c));
+        STANDARD_COLOURS.iter().chain(extra).map(|c| bag.get(*c)).product()
+    }
+}
+
+pub fn solve_one_with(games: &[Game], criterion: &dyn Criterion) -> u64 {
+    games.iter().filter(|g| (*g).is_possible(criterion)).map(|g|g.id).sum()
+}
+
+pub fn solve_one_with_limits(games: &[Game], limits: &Drawing) -> u64 {
+    solve_one_with(games, &MaxPerColour(limits.clone()))
+}
+
+pub fn solve_one(games: &[Game]) -> u64 {
+    solve_one_with_limits(games, &part_one_limits())
+}
+
+pub fn solve_two(games: &[Game]) -> u64 {
+    games.iter().map(|g| g.power()).sum()
+}
+
+pub fn parse_games<T: AsRef<str>>(input: T) -> Result<Vec<Game>, ParseGamesError> {
+    parse_games_with(input.as_ref(), ParseMode::global())
+}
+
+pub fn parse_games_with(input: &str, mode: ParseMode) -> Result<Vec<Game>, ParseGamesError> {
+    mode.parse_lines(input, Game::from_str).map_err(|(line, error)| ParseGamesError { line, error })
+}
+
+// Both answers from one pass over the input, holding a single game at a time.
+pub fn solve_streaming<R: BufRead>(reader: R, criterion: &dyn Criterion) -> Result<(u64, u64), ParseGamesError> {
+    solve_streaming_with(reader, criterion, ParseMode::global())
+}
+
+// Lines go through the same normalisation as ParseMode::lines.
+pub fn solve_streaming_with<R: BufRead>(reader: R, criterion: &dyn Criterion, mode: ParseMode) -> Result<(u64, u64), ParseGamesError> {
+    let (mut one, mut two) = (0, 0);
+    for (i, line) in reader.lines().enumerate() {
+        let error = |error| ParseGamesError { line: i + 1, error };
+        let line = line.map_err(|e| error(ParseGameError::Read(e.to_string())))?;
+        let Some(line) = mode.line(&line) else { continue };
+        let game: Game = match line.parse() {
+            Ok(game) => game,
+            Err(_) if mode == ParseMode::Lenient => continue,
+            Err(e) => return Err(error(e)),
+        };
+        if game.is_possible(criterion) {
+            one += game.id;
+        }
+        two += game.power();
+    }
+    Ok((one, two))
+}
+
+// Calls f with every (count, colour) in the game and returns its ID, without building any
+// Drawings. Follows the same rules as Game::from_str, and only allocates to report an error.
+pub fn fold_cube_counts<'a, F: FnMut(u64, &'a str)>(line: &'a str, mut f: F) -> Result<u64, ParseGameError> {
+    let (game_id, drawings) = line.split_once(": ").ok_or(ParseGameError::MissingColon)?;
+    let id = game_id.strip_prefix("Game ").ok_or_else(|| ParseGameError::InvalidId(game_id.to_string()))?;
+    let id: u64 = id.parse().map_err(|_| ParseGameError::InvalidId(id.to_string()))?;
+    for elem in drawings.split("; ").flat_map(|d| d.split(", ")) {
+        let (num, col) = elem.split_once(' ').ok_or_else(|| ParseGameError::Drawing(ParseDrawingError::MissingColour(elem.to_string())))?;
+        let n: u64 = num.parse().map_err(|_| ParseGameError::Drawing(ParseDrawingError::InvalidCount(num.to_string())))?;
+        f(n, colour_name(col).map_err(ParseGameError::Drawing)?);
+    }
+    Ok(id)
+}
+
+// The game's ID and its red, green and blue maxima, or None for the ID if it drew any other
+// colour.
+fn standard_maxima(line: &str) -> Result<Option<(u64, [u64; 3])>, ParseGameError> {
+    let mut maxima = [0; 3];
+    let mut other = false;
+    let id = fold_cube_counts(line, |n, colour| {
+        match colour {
+            "red" => maxima[0] = maxima[0].max(n),
+            "green" => maxima[1] = maxima[1].max(n),
+            "blue" => maxima[2] = maxima[2].max(n),
+            _ => other = true,
+        }
+    })?;
+    Ok(if other { None } else { Some((id, maxima)) })
+}
+
+// Both answers for the puzzle's bag straight from the text. Games drawing colours beyond red,
+// green and blue are rare enough to go through the full parser instead.
+pub fn solve_folding(input: &str) -> Result<(u64, u64), ParseGamesError> {
+    solve_folding_with(input, ParseMode::global())
+}
+
+// In lenient mode, lines that aren't games are left out, as in parse_games_with.
+pub fn solve_folding_with(input: &str, mode: ParseMode) -> Result<(u64, u64), ParseGamesError> {
+    let limits = [12, 13, 14];
+    let (mut one, mut two) = (0, 0);
+    for (i, line) in mode.lines(input) {
+        let error = |error| ParseGamesError { line: i, error };
+        let maxima = match standard_maxima(line) {
+            Ok(maxima) => maxima,
+            Err(_) if mode == ParseMode::Lenient => continue,
+            Err(e) => return Err(error(e)),
+        };
+        match maxima {
+            Some((id, maxima)) => {
+                if maxima.iter().zip(limits).all(|(max, limit)| *max <= limit) {
+                    one += id;
+                }
+                two += maxima.iter().product::<u64>();
+            },
+            None => {
+                // Any line standard_maxima accepts parses as a Game too.
+                let game: Game = line.parse().map_err(error)?;
+                if game.is_possible(&MaxPerColour(part_one_limits())) {
+                    one += game.id;
+                }
+                two += game.power();
+            },
+        }
+    }
+    Ok((one, two))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn parse_drawing() {
+        const TEST_INPUT: &str = r"3 blue, 4 red";
+        let drawing = Drawing::from_str(TEST_INPUT);
+        assert_eq!(Ok(Drawing::new([(RED, 4), (BLUE, 3)])), drawing)
+    }
+
+    #[test]
+    fn parse_drawings() {
+        const TEST_INPUT: &str = r"1 red, 2 green, 6 blue; 2 green";
+        let drawings: Vec<Drawing> = TEST_INPUT.split("; ").map(|s| Drawing::from_str(s).expect("parsing drawing")).collect();
+        assert_eq!(vec![Drawing::new([(RED, 1), (GREEN, 2), (BLUE, 6)]), Drawing::new([(GREEN, 2)])], drawings);
+    }
+
+    #[test]
+    fn test_parse_games() {
+        const TEST_INPUT: &str = r"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue";
+        assert_eq!(vec![
+            Game{ id: 1, drawings: vec![Drawing::new([(RED, 4), (BLUE, 3)]), Drawing::new([(RED, 1), (GREEN, 2), (BLUE, 6)]), Drawing::new([(GREEN, 2)])]},
+            Game{ id: 2, drawings: vec![Drawing::new([(GREEN, 2), (BLUE, 1)]), Drawing::new([(RED, 1), (GREEN, 3), (BLUE, 4)]), Drawing::new([(GREEN, 1), (BLUE, 1)])]},
+        ], parse_games(TEST_INPUT).unwrap());
+    }
+
+    #[test]
+    fn part_one() {
+        const TEST_INPUT: &str = r"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let games = parse_games(TEST_INPUT).unwrap();
+        assert_eq!(8, solve_one(&games));
+        assert_eq!(3, solve_one_with(&games, &MaxPerColour(part_one_limits()).and(criterion::TotalCubes(9))));
+        assert_eq!(15, solve_one_with_limits(&games, &Drawing::new([(RED, 100), (GREEN, 100), (BLUE, 100)])));
+        assert_eq!(0, solve_one_with_limits(&games, &Drawing::new([])));
+        assert_eq!(3, solve_one_with_limits(&games, &Drawing::new([(RED, 4), (GREEN, 3), (BLUE, 6)])));
+        assert!(Drawing::new([(RED, 12), (GREEN, 13), (BLUE, 14)]).fits_within(&part_one_limits()));
+        assert!(!Drawing::new([(GREEN, 14)]).fits_within(&part_one_limits()));
+    }
+
+    #[test]
+    fn extra_colours() {
+        let purple = ColorId::intern("purple");
+        assert_eq!(purple, ColorId::intern("purple"));
+        let games = parse_games("Game 1: 2 red, 1 purple; 3 green, 4 blue, 3 purple\nGame 2: 1 red, 1 green, 1 blue").unwrap();
+        assert_eq!(Drawing::new([(RED, 2), (GREEN, 3), (BLUE, 4), (purple, 3)]), games[0].minimum_bag());
+        assert_eq!(72, games[0].power());
+        assert_eq!(1, games[1].power());
+        assert_eq!(2, solve_one(&games));
+        assert_eq!(3, solve_one_with_limits(&games, &Drawing::new([(RED, 2), (GREEN, 3), (BLUE, 4), (purple, 3)])));
+        assert_eq!(0, parse_games("Game 1: 2 red, 3 blue").unwrap()[0].power());
+    }
+
+    #[test]
+    fn streaming() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let games = parse_games(input).unwrap();
+        assert_eq!(Ok((solve_one(&games), solve_two(&games))), solve_streaming(input.as_bytes(), &MaxPerColour(part_one_limits())));
+        assert_eq!(Ok((8, 2286)), solve_streaming(BufReader::with_capacity(8, input.as_bytes()), &MaxPerColour(part_one_limits())));
+        let error = solve_streaming(&b"Game 1: 1 red\nGame 2: \xff red"[..], &MaxPerColour(part_one_limits())).unwrap_err();
+        assert_eq!(2, error.line);
+        assert!(matches!(error.error, ParseGameError::Read(_)));
+    }
+
+    #[test]
+    fn violations() {
+        let game: Game = "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red; 15 green, 1 purple".parse().unwrap();
+        let limits = part_one_limits();
+        let violations: Vec<(usize, &Drawing)> = game.violations(&limits).collect();
+        assert_eq!(vec![(0, &game.drawings[0]), (3, &game.drawings[3])], violations);
+        assert_eq!(Drawing::new([(RED, 8)]), game.drawings[0].excess(&limits));
+        assert_eq!("20 red, 8 green, 6 blue", game.drawings[0].to_string());
+        assert_eq!("2 green, 1 purple", game.drawings[3].excess(&limits).to_string());
+        assert_eq!(0, game.violations(&Drawing::new([(RED, 20), (GREEN, 15), (BLUE, 6), (ColorId::intern("purple"), 1)])).count());
+    }
+
+    #[test]
+    fn lenient() {
+        let pasted = "Game 1: 3 blue, 4 red  \n\nnot a game\nGame 2: 1 red";
+        assert_eq!(Err(1), parse_games_with(pasted, ParseMode::Strict).map_err(|e| e.line));
+        let games = parse_games_with(pasted, ParseMode::Lenient).unwrap();
+        assert_eq!(vec![1, 2], games.iter().map(|g| g.id).collect::<Vec<_>>());
+        assert_eq!(Drawing::new([(BLUE, 3), (RED, 4)]), games[0].drawings[0]);
+        let criterion = MaxPerColour(part_one_limits());
+        assert_eq!(Ok((3, 0)), solve_streaming_with(pasted.as_bytes(), &criterion, ParseMode::Lenient));
+        assert_eq!(Err(1), solve_streaming_with(pasted.as_bytes(), &criterion, ParseMode::Strict).map_err(|e| e.line));
+        assert_eq!(Ok((3, 0)), solve_folding_with(pasted, ParseMode::Lenient));
+        assert_eq!(Ok((1, 4)), solve_folding_with("\nGame 1: 1 red, 2 blue; 2 green  \nGame 2: 1 purple\n# notes\n", ParseMode::Lenient));
+        assert_eq!(Err(1), solve_folding_with(pasted, ParseMode::Strict).map_err(|e| e.line));
+    }
+
+    #[test]
+    fn folding() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+Game 6: 2 red, 1 purple; 3 green, 4 blue
+Game 7: 2 red; 3 green, 4 blue";
+        let games = parse_games(input).unwrap();
+        assert_eq!(Ok((solve_one(&games), solve_two(&games))), solve_folding(input));
+        assert_eq!(Ok(Some((1, [4, 2, 6]))), standard_maxima("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"));
+        assert_eq!(Ok(None), standard_maxima("Game 6: 2 red, 1 purple; 3 green, 4 blue"));
+        let mut seen = Vec::new();
+        assert_eq!(Ok(9), fold_cube_counts("Game 9: 1 red, 2 blue; 3 red", |n, c| seen.push((n, c))));
+        assert_eq!(vec![(1, "red"), (2, "blue"), (3, "red")], seen);
+        for bad in ["Game 1 3 blue", "Game x: 3 blue", "Game 1: 3blue", "Game 1: x blue", "Game 1: 3 blue "] {
+            assert_eq!(bad.parse::<Game>().map(|g| g.id), fold_cube_counts(bad, |_, _| ()));
+        }
+        assert_eq!(2, solve_folding("Game 1: 1 red\nGame 2: x red").unwrap_err().line);
+    }
+
+    #[test]
+    fn stats() {
+        let games = parse_games("Game 7: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        let stats = games[0].stats(&MaxPerColour(part_one_limits()));
+        assert_eq!(GameStats {
+            id: 7,
+            drawings: 3,
+            possible: true,
+            minimum_bag: Drawing::new([(RED, 4), (GREEN, 2), (BLUE, 6)]),
+            power: 48,
+            totals: Drawing::new([(RED, 5), (GREEN, 4), (BLUE, 9)]),
+            total_cubes: 18,
+        }, stats);
+        assert_eq!(
+            r#"{"id":7,"drawings":3,"possible":true,"minimum_bag":{"red":4,"green":2,"blue":6},"power":48,"totals":{"red":5,"green":4,"blue":9},"total_cubes":18}"#,
+            serde_json::to_string(&stats).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Err(ParseDrawingError::InvalidCount("x".to_string())), "x red".parse::<Drawing>());
+        assert_eq!(Err(ParseDrawingError::MissingColour("4".to_string())), "3 blue, 4".parse::<Drawing>());
+        assert_eq!(Err(ParseGameError::MissingColon), "Game 1 3 blue".parse::<Game>());
+        assert_eq!(Err(ParseDrawingError::InvalidColour("".to_string())), "3 ".parse::<Drawing>());
+        assert_eq!(Err(ParseDrawingError::InvalidColour(" blue".to_string())), "3  blue".parse::<Drawing>());
+        let error = parse_games_with("Game 1: 3 blue, 4 red, 2 green  ", ParseMode::Strict).unwrap_err();
+        assert_eq!(ParseGamesError { line: 1, error: ParseGameError::Drawing(ParseDrawingError::InvalidColour("green  ".to_string())) }, error);
+        assert_eq!(Err(ParseGameError::InvalidId("Round 1".to_string())), "Round 1: 3 blue".parse::<Game>());
+        assert_eq!(Err(ParseGameError::InvalidId("-1".to_string())), "Game -1: 3 blue".parse::<Game>());
+        let error = parse_games("Game 1: 3 blue\nGame 2: 3 blue; 4 red,  5 green").unwrap_err();
+        assert_eq!(ParseGamesError { line: 2, error: ParseGameError::Drawing(ParseDrawingError::InvalidCount("".to_string())) }, error);
+        assert_eq!("line 2: \"\" is not a valid count", error.to_string());
+    }
+
+    #[test]
+    fn part_two() {
+        const TEST_INPUT: &str = r"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let games = parse_games(TEST_INPUT).unwrap();
+        assert_eq!(2286, solve_two(&games))
+    }
+
+}